@@ -0,0 +1,316 @@
+use super::{Bet, BettingTable, Outcome};
+use crate::{
+    player::Player,
+    teams::{Driver, DriverSet},
+};
+use std::collections::HashSet;
+
+/// Something wrong with a slip that placement validation did not catch,
+/// e.g. because the slip predates a newer validation rule
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SlipIssue {
+    /// The bet would not be accepted next to the bets placed before it
+    ClashesWithEarlierBet(Bet),
+    /// The driver is predicted to retire and to be classified at the same time
+    RetiresAndFinishes(Driver),
+}
+
+impl BettingTable {
+    /// Finds internally contradicting slips, ordered by player name
+    pub fn audit_slips(&self) -> Vec<(Player, SlipIssue)> {
+        let mut issues = Vec::new();
+
+        for (player, bets) in self.slips() {
+            for (index, bet) in bets.iter().enumerate() {
+                if !self.is_valid_against(bet, &bets[..index]) {
+                    issues.push((
+                        player.clone(),
+                        SlipIssue::ClashesWithEarlierBet(bet.clone()),
+                    ));
+                }
+            }
+
+            let retiring = bets.iter().filter_map(|bet| match bet {
+                Bet::DoesNotFinish(driver) => Some(*driver),
+                _ => None,
+            });
+            for driver in retiring {
+                let is_finishing = bets.iter().any(|bet| match bet {
+                    Bet::FinishPosition {
+                        driver: finisher, ..
+                    } => *finisher == driver,
+                    Bet::BothFinish(first, second) => *first == driver || *second == driver,
+                    _ => false,
+                });
+                if is_finishing {
+                    issues.push((player.clone(), SlipIssue::RetiresAndFinishes(driver)));
+                }
+            }
+        }
+        issues.sort_by(|(player, _), (other, _)| player.name.cmp(&other.name));
+
+        issues
+    }
+    /// Registered outcomes no placed bet picked, not even as a leg of a parlay or
+    /// a side of a conditional bet, most likely mis-entered since they pay nobody.
+    /// Outcomes only settling derived bets, e.g. finish positions behind a
+    /// BothFinish bet, are listed as well
+    pub fn unbacked_outcomes(&self) -> Vec<Outcome> {
+        let picks: Vec<&Bet> = self
+            .placed_bets
+            .values()
+            .flatten()
+            .flat_map(Bet::picks)
+            .collect();
+
+        self.outcomes
+            .iter()
+            .filter(|outcome| !picks.iter().any(|bet| self.is_winning_match(bet, outcome)))
+            .cloned()
+            .collect()
+    }
+    /// Whether every bet of the player, and every leg of its parlays, could win together
+    /// in a single race: no position or driver is taken twice, nobody both retires and
+    /// finishes and the predicted finishers fit the predicted finishers count
+    pub fn slip_feasible(&self, player: &Player) -> bool {
+        let slots = self.race.as_ref().map_or(20, |race| race.grid_size());
+        let bets = self.get_bets_for(player);
+        let predictions: Vec<&Bet> = bets
+            .iter()
+            .flat_map(|bet| match bet {
+                Bet::Parlay(legs) => legs.iter().collect(),
+                bet => vec![bet],
+            })
+            .collect();
+
+        let mut classified = DriverSet::default();
+        let mut retiring = DriverSet::default();
+        let mut positions = Vec::new();
+        for (index, bet) in predictions.iter().enumerate() {
+            if predictions[..index]
+                .iter()
+                .any(|earlier| bet.hedges(earlier))
+            {
+                return false;
+            }
+            match bet {
+                Bet::FinishPosition { driver, position } => {
+                    if classified.contains(*driver)
+                        || positions.contains(position)
+                        || position.value() > slots
+                    {
+                        return false;
+                    }
+                    classified.insert(*driver);
+                    positions.push(*position);
+                }
+                Bet::BothFinish(first, second) => {
+                    classified.insert(*first);
+                    classified.insert(*second);
+                }
+                Bet::ScoresPoints(driver) => classified.insert(*driver),
+                Bet::DoesNotFinish(driver) => retiring.insert(*driver),
+                Bet::ExactDNFs(drivers) => {
+                    for driver in drivers.drivers() {
+                        retiring.insert(driver);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let exact_dnfs = predictions.iter().find_map(|bet| match bet {
+            Bet::ExactDNFs(drivers) => Some(*drivers),
+            _ => None,
+        });
+        if exact_dnfs.is_some_and(|exact_dnfs| retiring != exact_dnfs)
+            || !classified.intersection(&retiring).is_empty()
+            || (classified.len() + retiring.len()) > slots as usize
+        {
+            return false;
+        }
+
+        // enough cars have to be classified to fill every predicted position
+        let needed = positions
+            .iter()
+            .map(|position| position.value() as usize)
+            .max()
+            .unwrap_or(0)
+            .max(classified.len());
+        let available = slots as usize - retiring.len();
+        let tolerance = self.config.finishers_count_tolerance as usize;
+        predictions.iter().all(|bet| match bet {
+            Bet::FinishersCount(count) => {
+                let count = *count as usize;
+                count.saturating_sub(tolerance).max(needed) <= (count + tolerance).min(available)
+            }
+            _ => true,
+        })
+    }
+    /// Groups the players who placed exactly the same bets, in any order, e.g. to spot
+    /// players copying each other. Players without bets and slips nobody else placed
+    /// are left out, groups and the players in them are ordered by name
+    pub fn identical_slips(&self) -> Vec<Vec<Player>> {
+        let mut groups: Vec<(HashSet<&Bet>, Vec<Player>)> = Vec::new();
+
+        for (player, bets) in self.slips().filter(|(_, bets)| !bets.is_empty()) {
+            let bets: HashSet<&Bet> = bets.iter().collect();
+            match groups.iter_mut().find(|(slip, _)| *slip == bets) {
+                Some((_, players)) => players.push(player.clone()),
+                None => groups.push((bets, vec![player.clone()])),
+            }
+        }
+
+        let mut groups: Vec<Vec<Player>> = groups
+            .into_iter()
+            .map(|(_, players)| players)
+            .filter(|players| players.len() >= 2)
+            .collect();
+        for players in &mut groups {
+            players.sort_by(|player, other| player.name.cmp(&other.name));
+        }
+        groups.sort_by(|group, other| group[0].name.cmp(&other[0].name));
+
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SlipIssue;
+    use crate::{
+        bets::{Bet, BettingTable, Outcome},
+        player::Player,
+        race::Position,
+        teams::Driver,
+    };
+
+    #[test]
+    fn slip_predicting_more_finishers_than_the_count_is_not_feasible() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(Bet::FinishersCount(15), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(
+                Bet::FinishPosition {
+                    driver: Driver::VER,
+                    position: Position::new(15),
+                },
+                &player
+            )
+            .is_ok());
+        assert!(betting_table.slip_feasible(&player));
+
+        assert!(betting_table
+            .place(
+                Bet::FinishPosition {
+                    driver: Driver::ALB,
+                    position: Position::new(18),
+                },
+                &player
+            )
+            .is_ok());
+        assert!(!betting_table.slip_feasible(&player));
+    }
+    #[test]
+    fn slip_retiring_more_drivers_than_the_count_allows_is_not_feasible() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(Bet::FinishersCount(18), &player)
+            .is_ok());
+        for driver in [Driver::ALB, Driver::LAT, Driver::MSC] {
+            assert!(betting_table
+                .place(Bet::DoesNotFinish(driver), &player)
+                .is_ok());
+        }
+
+        assert!(!betting_table.slip_feasible(&player));
+    }
+    #[test]
+    fn outcomes_nobody_picked_are_unbacked() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(
+                Bet::Parlay(vec![
+                    Bet::FastestLap(Driver::LEC),
+                    Bet::PolePosition(Driver::LEC)
+                ]),
+                &player
+            )
+            .is_ok());
+        let backed = Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        };
+        let unbacked = Outcome {
+            outcome: Bet::DriverOfTheDay(Driver::ALB),
+            reward: 100,
+            registered_at: None,
+        };
+        betting_table.register_outcome(backed);
+        betting_table.register_outcome(unbacked.clone());
+
+        assert_eq!(betting_table.unbacked_outcomes(), vec![unbacked]);
+    }
+    #[test]
+    fn audit_finds_contradicting_slips() {
+        let mut betting_table = BettingTable::new();
+        let valid = Player::create("valid".into(), None);
+        let contradicting = Player::create("contradicting".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &valid)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &valid)
+            .is_ok());
+
+        // bypass placement validation, like a slip placed under older rules
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::ALB,
+            position: Position::new(10),
+        };
+        let id = betting_table.players.intern(&contradicting);
+        betting_table.placed_bets.insert(
+            id,
+            vec![
+                Bet::WillHaveSafetyCar(true),
+                Bet::WillHaveSafetyCar(false),
+                Bet::DoesNotFinish(Driver::ALB),
+                finish_position,
+            ],
+        );
+
+        assert_eq!(
+            betting_table.audit_slips(),
+            vec![
+                (
+                    contradicting.clone(),
+                    SlipIssue::ClashesWithEarlierBet(Bet::WillHaveSafetyCar(false))
+                ),
+                (contradicting, SlipIssue::RetiresAndFinishes(Driver::ALB)),
+            ]
+        );
+    }
+    #[test]
+    fn players_with_the_same_bets_share_a_group() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        let nuyts = Player::create("nuyts".into(), None);
+        for bet in [Bet::FastestLap(Driver::VER), Bet::WillHaveSafetyCar(true)] {
+            assert!(betting_table.place(bet, &michiel).is_ok());
+        }
+        for bet in [Bet::WillHaveSafetyCar(true), Bet::FastestLap(Driver::VER)] {
+            assert!(betting_table.place(bet, &demi).is_ok());
+        }
+        for bet in [Bet::FastestLap(Driver::VER), Bet::WillHaveSafetyCar(false)] {
+            assert!(betting_table.place(bet, &nuyts).is_ok());
+        }
+
+        assert_eq!(betting_table.identical_slips(), vec![vec![demi, michiel]]);
+    }
+}
@@ -0,0 +1,147 @@
+use super::BetCategory;
+use std::collections::HashMap;
+
+/// Rules a betting pool can tweak, the defaults give a standard pool
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolConfig {
+    /// How many cars a FinishersCount bet may be off and still win
+    pub finishers_count_tolerance: u8,
+    /// How many laps a FirstSafetyCarLap bet may be off and still win
+    pub safety_car_lap_tolerance: u8,
+    /// How many overtakes an OvertakeCount bet may be off and still win
+    pub overtake_count_tolerance: u32,
+    /// How many stops a PitStops bet may be off and still win
+    pub pit_stops_tolerance: u8,
+    /// How many players have to join before the pool starts scoring
+    pub min_players: usize,
+    /// Reward for predicting the exact set of retirements
+    pub exact_dnfs_reward: u64,
+    /// Reward for a pair of drivers that both finish the race
+    pub both_finish_reward: u64,
+    /// Reward for a driver finishing ahead of the pole sitter
+    pub beats_pole_sitter_reward: u64,
+    /// Reward for picking the best finishing rookie
+    pub best_rookie_reward: u64,
+    /// Reward for a team finishing first and second
+    pub constructor_one_two_reward: u64,
+    /// Reward for the team with the best placed finish in the points
+    pub first_team_to_score_reward: u64,
+    /// Reward for a driver finishing in the points
+    pub scores_points_reward: u64,
+    /// How many times its reward a correctly called wet race pays, since it is rarer
+    pub wet_race_reward_factor: u64,
+    /// Reward for the exact grid slot a driver starts from
+    pub start_position_reward: u64,
+    /// Reward for a negated bet that did not happen
+    pub negation_reward: u64,
+    /// What placing a single bet costs a player
+    pub stake_per_bet: u64,
+    /// What every player starts with to stake bets from through `place_with_stake`
+    pub bankroll: u64,
+    /// What a bet paid by a single outcome is projected to earn when assumed to hit
+    pub projected_reward: u64,
+    /// Reward of the finish positions read from a results feed by `from_f1_json`
+    pub feed_position_reward: u64,
+    /// Reward of the fastest lap read from a results feed by `from_f1_json`
+    pub feed_fastest_lap_reward: u64,
+    /// Reward of the retirements read from a results feed by `from_f1_json`
+    pub feed_retirement_reward: u64,
+    /// How fractional scores are turned into whole points
+    pub rounding: RoundingMode,
+    /// How many seconds a player has to wait between two placements, None to never wait
+    pub placement_cooldown: Option<u64>,
+    /// From which moment, in seconds, bets of a category are no longer accepted.
+    /// Categories without a deadline stay open
+    pub deadlines: HashMap<BetCategory, u64>,
+    /// The most a single player can win, multiplier included.
+    /// Anything above it is forfeited, not shared with the other players
+    pub max_winnings: Option<u64>,
+    /// Whether a player may answer a single pick question more than once,
+    /// e.g. bet on a safety car and on no safety car
+    pub allow_hedging: bool,
+    /// Which winning bets count towards the score of a player
+    pub scoring: ScoringMode,
+    /// How many bets a player can have on a single driver before the diversification
+    /// penalty kicks in
+    pub max_bets_per_driver: usize,
+    /// Factor applied on the winnings of every bet on a driver the player has more than
+    /// `max_bets_per_driver` bets on, e.g. 0.5 to halve them. None to never penalize
+    pub diversification_penalty: Option<f64>,
+}
+
+/// How a fractional score, e.g. a share of a reward after its multiplier,
+/// is collapsed into whole points
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum RoundingMode {
+    /// Round down, a fraction of a point is never paid out
+    #[default]
+    Floor,
+    /// Round up, any fraction pays a full point
+    Ceil,
+    /// Round to the nearest point, halves round away from zero
+    Nearest,
+}
+
+/// How the rewards of the winning bets of a player add up to a score
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ScoringMode {
+    /// Every winning bet counts
+    #[default]
+    Sum,
+    /// Only the best paying bet of every category counts, to limit runaway totals
+    BestPerCategory,
+}
+
+impl RoundingMode {
+    /// Collapses a score of `numerator / denominator` points into whole points,
+    /// only the fractional part is rounded
+    pub fn apply(&self, numerator: u128, denominator: u128) -> u128 {
+        let (whole, fraction) = (numerator / denominator, numerator % denominator);
+        let round_up = match self {
+            RoundingMode::Floor => false,
+            RoundingMode::Ceil => fraction > 0,
+            RoundingMode::Nearest => fraction >= denominator - fraction,
+        };
+
+        whole + u128::from(round_up)
+    }
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            finishers_count_tolerance: 0,
+            safety_car_lap_tolerance: 0,
+            overtake_count_tolerance: 0,
+            pit_stops_tolerance: 0,
+            min_players: 0,
+            exact_dnfs_reward: 1000,
+            both_finish_reward: 200,
+            beats_pole_sitter_reward: 300,
+            best_rookie_reward: 250,
+            constructor_one_two_reward: 1500,
+            first_team_to_score_reward: 150,
+            scores_points_reward: 50,
+            wet_race_reward_factor: 3,
+            start_position_reward: 100,
+            negation_reward: 50,
+            stake_per_bet: 1,
+            bankroll: 1000,
+            projected_reward: 100,
+            feed_position_reward: 100,
+            feed_fastest_lap_reward: 50,
+            feed_retirement_reward: 50,
+            rounding: RoundingMode::default(),
+            placement_cooldown: None,
+            deadlines: HashMap::new(),
+            max_winnings: None,
+            allow_hedging: false,
+            scoring: ScoringMode::default(),
+            max_bets_per_driver: 3,
+            diversification_penalty: None,
+        }
+    }
+}
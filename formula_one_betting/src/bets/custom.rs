@@ -0,0 +1,157 @@
+use super::{
+    errors::{BetError, ScoringOverflow},
+    BettingTable, Outcome,
+};
+use crate::player::Player;
+use std::{any::Any, sync::Arc};
+
+/// A kind of bet defined outside of this crate, placed next to the built-in bets.
+/// Custom bets are settled by the registered outcomes, paying their reward
+pub trait CustomBet: Send + Sync {
+    /// Whether the outcome wins this bet
+    fn settles_against(&self, outcome: &Outcome) -> bool;
+    /// Whether a player can't have this bet next to the other custom bet
+    fn clashes_with(&self, other: &dyn CustomBet) -> bool;
+    /// The bet itself, so `clashes_with` can downcast the other bet to its own type
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl BettingTable {
+    /// Places a custom bet, rejected when it clashes with a custom bet the player
+    /// already placed. The player joins the pool like with any other bet
+    pub fn place_custom(
+        &mut self,
+        bet: impl CustomBet + 'static,
+        player: &Player,
+    ) -> Result<(), BetError> {
+        let id = self.players.id_of(player);
+        let clashes = id
+            .and_then(|id| self.custom_bets.get(&id))
+            .is_some_and(|bets| bets.iter().any(|placed| bet.clashes_with(placed.as_ref())));
+        if clashes {
+            return Err(BetError::ClashesWithCustomBet);
+        }
+
+        let id = self.players.intern(player);
+        self.custom_bets.entry(id).or_default().push(Arc::new(bet));
+
+        Ok(())
+    }
+    /// How many custom bets the player placed
+    pub fn custom_bet_count(&self, player: &Player) -> usize {
+        self.players
+            .id_of(player)
+            .and_then(|id| self.custom_bets.get(&id))
+            .map_or(0, Vec::len)
+    }
+    /// What the custom bets of every player won, multiplier included
+    pub(super) fn custom_winnings(&self) -> Result<Vec<(Player, u64)>, ScoringOverflow> {
+        let mut winnings = Vec::new();
+        if !self.is_active() {
+            return Ok(winnings);
+        }
+
+        for (id, bets) in &self.custom_bets {
+            let player = self.players.player(*id);
+            for bet in bets {
+                for outcome in self
+                    .outcomes
+                    .iter()
+                    .filter(|outcome| bet.settles_against(outcome))
+                {
+                    let amount = outcome
+                        .reward
+                        .checked_mul(player.multiplier.value())
+                        .ok_or_else(|| ScoringOverflow {
+                            player: player.clone(),
+                        })?;
+                    winnings.push((player.clone(), amount));
+                }
+            }
+        }
+
+        Ok(winnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CustomBet;
+    use crate::{
+        bets::{errors::BetError, Bet, BettingTable, Outcome},
+        player::{Multiplier, Player},
+        race::Position,
+        teams::Driver,
+    };
+    use std::any::Any;
+
+    /// The driver finishes in the top 3
+    struct Podium(Driver);
+
+    impl CustomBet for Podium {
+        fn settles_against(&self, outcome: &Outcome) -> bool {
+            matches!(
+                outcome.outcome,
+                Bet::FinishPosition { driver, position }
+                    if driver == self.0 && position.value() <= 3
+            )
+        }
+        fn clashes_with(&self, other: &dyn CustomBet) -> bool {
+            other
+                .as_any()
+                .downcast_ref::<Podium>()
+                .is_some_and(|other| other.0 == self.0)
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn custom_bets_score_and_clash() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), Some(Multiplier::X2));
+        assert!(betting_table
+            .place_custom(Podium(Driver::VER), &player)
+            .is_ok());
+        assert_eq!(
+            betting_table.place_custom(Podium(Driver::VER), &player),
+            Err(BetError::ClashesWithCustomBet)
+        );
+        assert!(betting_table
+            .place_custom(Podium(Driver::HAM), &player)
+            .is_ok());
+        assert_eq!(betting_table.custom_bet_count(&player), 2);
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(2),
+            },
+            reward: 100,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::HAM,
+                position: Position::new(4),
+            },
+            reward: 100,
+            registered_at: None,
+        });
+
+        assert_eq!(betting_table.results().get(&player), Some(&200));
+    }
+    #[test]
+    fn custom_bets_alone_join_the_pool() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place_custom(Podium(Driver::VER), &player)
+            .is_ok());
+
+        assert_eq!(betting_table.player_count(), 1);
+        assert_eq!(betting_table.prune_empty_players(), 0);
+        assert_eq!(betting_table.player_count(), 1);
+    }
+}
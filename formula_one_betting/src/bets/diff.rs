@@ -0,0 +1,91 @@
+use super::{Bet, BettingTable, Outcome};
+use crate::player::Player;
+
+/// Everything that changed between two states of a betting table
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct TableDiff {
+    /// Bets that were placed since the earlier state
+    pub placed: Vec<(Player, Bet)>,
+    /// Bets that were cancelled since the earlier state
+    pub cancelled: Vec<(Player, Bet)>,
+    /// Outcomes that were registered since the earlier state
+    pub registered: Vec<Outcome>,
+}
+
+impl BettingTable {
+    /// Lists what changed compared to an earlier snapshot of this table
+    pub fn diff(&self, earlier: &BettingTable) -> TableDiff {
+        let mut diff = TableDiff::default();
+
+        for (player, bets) in self.slips() {
+            let earlier_bets = earlier.get_bets_for(player);
+            for bet in bets.iter().filter(|bet| !earlier_bets.contains(bet)) {
+                diff.placed.push((player.clone(), bet.clone()));
+            }
+        }
+        for (player, bets) in earlier.slips() {
+            let current_bets = self.get_bets_for(player);
+            for bet in bets.iter().filter(|bet| !current_bets.contains(bet)) {
+                diff.cancelled.push((player.clone(), bet.clone()));
+            }
+        }
+
+        // outcomes can be registered more than once, so match them one by one
+        let mut earlier_outcomes = earlier.outcomes.clone();
+        for outcome in &self.outcomes {
+            match earlier_outcomes
+                .iter()
+                .position(|earlier| earlier == outcome)
+            {
+                Some(index) => {
+                    earlier_outcomes.remove(index);
+                }
+                None => diff.registered.push(outcome.clone()),
+            }
+        }
+
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TableDiff;
+    use crate::{
+        bets::{Bet, BettingTable, Outcome},
+        player::Player,
+        teams::Driver,
+    };
+
+    #[test]
+    fn diff_lists_exactly_the_changes_since_the_snapshot() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let kept_bet = Bet::FastestLap(Driver::LEC);
+        let cancelled_bet = Bet::DoesNotFinish(Driver::ALB);
+        assert!(betting_table.place(kept_bet, &player).is_ok());
+        assert!(betting_table.place(cancelled_bet.clone(), &player).is_ok());
+
+        let snapshot = betting_table.clone();
+        assert_eq!(betting_table.diff(&snapshot), TableDiff::default());
+
+        let new_bet = Bet::WillHaveSafetyCar(true);
+        assert!(betting_table.place(new_bet.clone(), &player).is_ok());
+        assert!(betting_table.cancel(&cancelled_bet, &player).is_ok());
+        let outcome = Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        };
+        betting_table.register_outcome(outcome.clone());
+
+        assert_eq!(
+            betting_table.diff(&snapshot),
+            TableDiff {
+                placed: vec![(player.clone(), new_bet)],
+                cancelled: vec![(player, cancelled_bet)],
+                registered: vec![outcome],
+            }
+        );
+    }
+}
@@ -3,9 +3,15 @@ use std::{
     fmt::{self, Display, Formatter},
 };
 
-use super::Bet;
+use super::{Bet, BetCategory, Outcome};
+use crate::{
+    player::Player,
+    race::Position,
+    teams::{Constructor, Driver},
+};
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClashesWithExistingBet {
     pub existing_bet: Bet,
 }
@@ -17,3 +23,160 @@ impl Display for ClashesWithExistingBet {
         write!(f, "This player already placed a bet for this betType")
     }
 }
+
+/// Everything that can go wrong when changing the bets of a player
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum BetError {
+    /// The new bet clashes with a bet the player already placed
+    ClashesWithExistingBet(ClashesWithExistingBet),
+    /// The player never placed this bet
+    BetNotFound(Bet),
+    /// The position is beyond the number of cars on the grid of the race
+    PositionOutOfGrid(Position),
+    /// The player placed a bet too recently, placing is allowed again from the given moment
+    RateLimited(u64),
+    /// The deadline for bets of this category has passed
+    BettingClosed(BetCategory),
+    /// The driver is not in the first season of the race
+    NotARookie(Driver),
+    /// The table has no race, so there is no season to tell the rookies apart
+    SeasonUnknown,
+    /// The stake is more than the bankroll the player has left, which is given
+    InsufficientBankroll(u64),
+    /// The new custom bet clashes with a custom bet the player already placed
+    ClashesWithCustomBet,
+    /// The player already used the key of an idempotent placement for the given bet
+    KeyReused(Bet),
+}
+
+impl Error for BetError {}
+
+impl Display for BetError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            BetError::ClashesWithExistingBet(error) => error.fmt(f),
+            BetError::BetNotFound(_) => write!(f, "This player did not place this bet"),
+            BetError::PositionOutOfGrid(position) => {
+                write!(f, "Not enough cars on the grid for P{}", position.value())
+            }
+            BetError::RateLimited(retry_at) => {
+                write!(f, "This player bets too fast, try again at {retry_at}")
+            }
+            BetError::BettingClosed(category) => {
+                write!(f, "Betting on {category:?} bets is closed")
+            }
+            BetError::NotARookie(driver) => write!(f, "{driver:?} is not a rookie this season"),
+            BetError::SeasonUnknown => {
+                write!(f, "Rookie bets need a race to know the season")
+            }
+            BetError::InsufficientBankroll(remaining) => {
+                write!(f, "This player has only {remaining} left to stake")
+            }
+            BetError::ClashesWithCustomBet => {
+                write!(f, "This player already placed a clashing custom bet")
+            }
+            BetError::KeyReused(bet) => {
+                write!(f, "This key was already used to place {bet:?}")
+            }
+        }
+    }
+}
+
+impl From<ClashesWithExistingBet> for BetError {
+    fn from(error: ClashesWithExistingBet) -> Self {
+        BetError::ClashesWithExistingBet(error)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct OutcomeNotFound {
+    pub outcome: Outcome,
+}
+
+impl Error for OutcomeNotFound {}
+
+impl Display for OutcomeNotFound {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "This outcome was never registered")
+    }
+}
+
+/// A slip could not be read, pointing at the part that is not understood
+#[derive(Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub token: String,
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Could not read \"{}\" as a bet", self.token)
+    }
+}
+
+/// The score of a player no longer fits in a whole number of points
+#[derive(Debug, Eq, PartialEq)]
+pub struct ScoringOverflow {
+    pub player: Player,
+}
+
+impl Error for ScoringOverflow {}
+
+impl Display for ScoringOverflow {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "The score of {} is too large to count", self.player.name)
+    }
+}
+
+/// Everything that can go wrong when adding up the scores
+#[derive(Debug, PartialEq)]
+pub enum ScoringError {
+    /// The score of a player no longer fits in a whole number of points
+    Overflow(ScoringOverflow),
+    /// The configured diversification penalty is not a finite factor of at least 0
+    InvalidPenalty(f64),
+    /// The summed score of the supporters of a team no longer fits in a whole number of points
+    TeamOverflow(Constructor),
+}
+
+impl Error for ScoringError {}
+
+impl Display for ScoringError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ScoringError::Overflow(error) => error.fmt(f),
+            ScoringError::InvalidPenalty(penalty) => {
+                write!(f, "{penalty} is not a valid diversification penalty")
+            }
+            ScoringError::TeamOverflow(team) => {
+                write!(
+                    f,
+                    "The score of the supporters of {team:?} is too large to count"
+                )
+            }
+        }
+    }
+}
+
+impl From<ScoringOverflow> for ScoringError {
+    fn from(error: ScoringOverflow) -> Self {
+        ScoringError::Overflow(error)
+    }
+}
+
+/// A probability outside of 0 to 1 was given for a bet
+#[derive(Debug, PartialEq)]
+pub struct InvalidProbability {
+    pub bet: Bet,
+    pub probability: f64,
+}
+
+impl Error for InvalidProbability {}
+
+impl Display for InvalidProbability {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} is not a probability", self.probability)
+    }
+}
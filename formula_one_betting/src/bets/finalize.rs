@@ -0,0 +1,225 @@
+use super::{errors::ScoringError, ledger::LedgerEntry, BettingTable};
+use crate::player::Player;
+use sha2::{Digest, Sha256};
+
+/// The official record of a finished pool, it can no longer change
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalResults {
+    standings: Vec<(Player, u64)>,
+    ledger: Vec<LedgerEntry>,
+    hash: [u8; 32],
+}
+
+impl FinalResults {
+    /// The final results ordered from the highest to the lowest score
+    pub fn standings(&self) -> &[(Player, u64)] {
+        &self.standings
+    }
+    /// Every reward that makes up the standings
+    pub fn ledger(&self) -> &[LedgerEntry] {
+        &self.ledger
+    }
+    /// SHA-256 fingerprint of the pool rules, race, bets, stakes, bankers, voided bets,
+    /// outcomes and standings the results
+    /// were built from, taken over their stored format so it is the same on every machine.
+    /// Any change to them gives a different hash, custom bets are not part of it
+    pub fn hash(&self) -> [u8; 32] {
+        self.hash
+    }
+}
+
+impl BettingTable {
+    /// Closes the pool for good, the table is consumed so nothing can change afterwards.
+    /// Panics when a score overflows, see `try_finalize` to handle that instead
+    pub fn finalize(self) -> FinalResults {
+        self.try_finalize().expect("Scores fit in a u64")
+    }
+    /// Closes the pool for good, or gives an error when a score no longer fits in a u64
+    pub fn try_finalize(self) -> Result<FinalResults, ScoringError> {
+        let standings = self.try_leaderboard()?;
+
+        // hash maps encode in no particular order, so their entries are encoded one by one
+        // and sorted on those bytes, which also tells apart players with the same name
+        let sorted = |encoded: &mut dyn Iterator<Item = Vec<u8>>| {
+            let mut encoded: Vec<Vec<u8>> = encoded.collect();
+            encoded.sort();
+            encoded
+        };
+        let mut config = self.config.clone();
+        let deadlines = sorted(&mut config.deadlines.drain().map(|deadline| encode(&deadline)));
+        let slips = sorted(&mut self.slips().map(|slip| encode(&slip)));
+        let stakes = sorted(
+            &mut self
+                .stakes
+                .iter()
+                .map(|((id, bet), stake)| encode(&(self.players.player(*id), bet, stake))),
+        );
+        let bankers = sorted(
+            &mut self
+                .bankers
+                .iter()
+                .map(|(id, bet)| encode(&(self.players.player(*id), bet))),
+        );
+        let voided_bets = sorted(
+            &mut self
+                .voided_bets
+                .iter()
+                .map(|(id, bets)| encode(&(self.players.player(*id), bets))),
+        );
+        let ranks = sorted(&mut standings.iter().map(encode));
+        let hash = Sha256::digest(encode(&(
+            (config, deadlines, &self.race),
+            (slips, stakes, bankers, voided_bets),
+            &self.outcomes,
+            ranks,
+        )));
+
+        Ok(FinalResults {
+            ledger: self.try_ledger()?,
+            hash: hash.into(),
+            standings,
+        })
+    }
+}
+
+/// The stored format of a part of the table
+fn encode(value: &impl serde::Serialize) -> Vec<u8> {
+    bincode::serialize(value).expect("A betting table can always be encoded")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bets::{config::PoolConfig, Bet, BettingTable, Outcome},
+        player::Player,
+        race::{Location, Race},
+        teams::{Constructor, Driver},
+    };
+    use std::collections::HashMap;
+
+    fn finished_table(reward: u64) -> BettingTable {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::VER), &demi)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward,
+            registered_at: None,
+        });
+
+        betting_table
+    }
+
+    #[test]
+    fn final_standings_match_the_results() {
+        let betting_table = finished_table(100);
+        let results = betting_table.results();
+        let ledger = betting_table.ledger();
+
+        let final_results = betting_table.finalize();
+        assert_eq!(
+            final_results
+                .standings()
+                .iter()
+                .cloned()
+                .collect::<HashMap<Player, u64>>(),
+            results
+        );
+        assert_eq!(final_results.ledger(), ledger.as_slice());
+    }
+    #[test]
+    fn hash_changes_with_the_inputs() {
+        assert_eq!(
+            finished_table(100).finalize().hash(),
+            finished_table(100).finalize().hash()
+        );
+        assert_ne!(
+            finished_table(100).finalize().hash(),
+            finished_table(50).finalize().hash()
+        );
+    }
+    #[test]
+    fn hash_covers_the_rules_and_the_full_player() {
+        let mut stricter = BettingTable::with_config(PoolConfig {
+            max_winnings: Some(50),
+            ..Default::default()
+        });
+        let michiel = Player::create("michiel".into(), None);
+        assert!(stricter
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert_ne!(
+            stricter.finalize().hash(),
+            finished_table(100).finalize().hash()
+        );
+
+        let namesakes = |order: [Option<Constructor>; 2]| {
+            let mut betting_table = BettingTable::new();
+            for favorite_team in order {
+                let mut player = Player::create("michiel".into(), None);
+                player.favorite_team = favorite_team;
+                assert!(betting_table
+                    .place(Bet::FastestLap(Driver::LEC), &player)
+                    .is_ok());
+            }
+            betting_table.finalize().hash()
+        };
+        assert_eq!(
+            namesakes([None, Some(Constructor::Ferrari)]),
+            namesakes([Some(Constructor::Ferrari), None])
+        );
+        assert_ne!(
+            namesakes([None, Some(Constructor::Ferrari)]),
+            namesakes([None, Some(Constructor::McLaren)])
+        );
+    }
+    #[test]
+    fn hash_covers_the_race_stakes_bankers_and_voided_bets() {
+        let michiel = Player::create("michiel".into(), None);
+        let original = finished_table(100).finalize().hash();
+
+        let mut betting_table = finished_table(100);
+        betting_table.race = Some(Race::new(Location::Monaco, 2022));
+        assert_ne!(betting_table.finalize().hash(), original);
+
+        let mut betting_table = finished_table(100);
+        assert!(betting_table
+            .place_with_stake(Bet::PolePosition(Driver::HAM), &michiel, 5)
+            .is_ok());
+        let mut unstaked = finished_table(100);
+        assert!(unstaked
+            .place(Bet::PolePosition(Driver::HAM), &michiel)
+            .is_ok());
+        assert_ne!(betting_table.finalize().hash(), unstaked.finalize().hash());
+
+        let mut betting_table = finished_table(100);
+        assert!(betting_table
+            .set_banker(&michiel, &Bet::FastestLap(Driver::LEC))
+            .is_ok());
+        assert_ne!(betting_table.finalize().hash(), original);
+
+        let mut betting_table = finished_table(100);
+        assert!(betting_table
+            .place(Bet::PolePosition(Driver::HAM), &michiel)
+            .is_ok());
+        betting_table.void_bets_matching(|bet| *bet == Bet::PolePosition(Driver::HAM));
+        assert_ne!(betting_table.finalize().hash(), original);
+    }
+    #[test]
+    fn hash_is_pinned() {
+        let hash = finished_table(100).finalize().hash();
+        let hex: String = hash.iter().map(|byte| format!("{byte:02x}")).collect();
+
+        // the hash of a stored record must never change, not even across platforms
+        assert_eq!(
+            hex,
+            "aabe88ee7a7e79e1e4e903d143cbc03fd34459248c5e6e8231881e5a6653e5ea"
+        );
+    }
+}
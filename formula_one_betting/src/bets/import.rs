@@ -0,0 +1,223 @@
+use super::{config::PoolConfig, Bet, Outcome};
+use crate::{
+    race::Position,
+    teams::{Driver, DriverSet, UnknownDriver},
+};
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// A results feed that can't be turned into outcomes
+#[derive(Debug, Eq, PartialEq)]
+pub enum ImportError {
+    /// The feed is not a JSON array of records
+    NotAFeed,
+    /// The record at this index of the feed can't be read
+    InvalidRecord { index: usize, reason: String },
+}
+
+impl Error for ImportError {}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ImportError::NotAFeed => write!(f, "The feed is not a list of results"),
+            ImportError::InvalidRecord { index, reason } => {
+                write!(f, "Result {index} of the feed is invalid: {reason}")
+            }
+        }
+    }
+}
+
+/// The end result of a single driver in a feed
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct F1Record {
+    driver: String,
+    position: Option<u8>,
+    #[serde(default)]
+    fastest_lap: bool,
+    #[serde(default)]
+    dnf: bool,
+}
+
+impl Outcome {
+    /// Reads a results feed, each outcome paying the reward of a standard pool
+    /// for its kind of bet. The feed is a JSON
+    /// array with a record per driver, only the driver code is required:
+    ///
+    /// ```json
+    /// [
+    ///     { "driver": "VER", "position": 1, "fastestLap": true },
+    ///     { "driver": "ALB", "position": null, "dnf": true }
+    /// ]
+    /// ```
+    ///
+    /// A position gives a FinishPosition outcome, `dnf` a DoesNotFinish outcome
+    /// and `fastestLap` a FastestLap outcome. Records listing a driver twice, a position
+    /// taken by another driver or both a position and a retirement are rejected
+    pub fn from_f1_json(data: &str) -> Result<Vec<Outcome>, ImportError> {
+        Self::from_f1_json_with(data, &PoolConfig::default())
+    }
+    /// Reads a results feed like `from_f1_json`, each outcome paying the feed reward
+    /// the given pool configures for its kind of bet
+    pub fn from_f1_json_with(data: &str, config: &PoolConfig) -> Result<Vec<Outcome>, ImportError> {
+        let records: Vec<serde_json::Value> =
+            serde_json::from_str(data).map_err(|_| ImportError::NotAFeed)?;
+
+        let mut outcomes = Vec::new();
+        let mut drivers = DriverSet::default();
+        let mut positions = Vec::new();
+        for (index, record) in records.into_iter().enumerate() {
+            let invalid = |reason: String| ImportError::InvalidRecord { index, reason };
+            let record: F1Record =
+                serde_json::from_value(record).map_err(|error| invalid(error.to_string()))?;
+            let driver: Driver = record
+                .driver
+                .parse()
+                .map_err(|error: UnknownDriver| invalid(error.to_string()))?;
+            if drivers.contains(driver) {
+                return Err(invalid(format!("{driver:?} is listed more than once")));
+            }
+            drivers.insert(driver);
+
+            if let Some(position) = record.position {
+                if !(1..=20).contains(&position) {
+                    return Err(invalid(format!("P{position} is not on the grid")));
+                }
+                if record.dnf {
+                    return Err(invalid(format!(
+                        "{driver:?} can't finish P{position} and retire"
+                    )));
+                }
+                if positions.contains(&position) {
+                    return Err(invalid(format!("P{position} is taken by another driver")));
+                }
+                positions.push(position);
+                outcomes.push((
+                    Bet::FinishPosition {
+                        driver,
+                        position: Position::new(position),
+                    },
+                    config.feed_position_reward,
+                ));
+            }
+            if record.dnf {
+                outcomes.push((Bet::DoesNotFinish(driver), config.feed_retirement_reward));
+            }
+            if record.fastest_lap {
+                outcomes.push((Bet::FastestLap(driver), config.feed_fastest_lap_reward));
+            }
+        }
+
+        Ok(outcomes
+            .into_iter()
+            .map(|(outcome, reward)| Outcome {
+                outcome,
+                reward,
+                registered_at: None,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportError;
+    use crate::{
+        bets::{config::PoolConfig, Bet, Outcome},
+        race::Position,
+        teams::Driver,
+    };
+
+    #[test]
+    fn a_feed_turns_into_outcomes() {
+        let feed = r#"[
+            { "driver": "VER", "position": 1, "fastestLap": true, "dnf": false },
+            { "driver": "lec", "position": 2 },
+            { "driver": "ALB", "position": null, "dnf": true }
+        ]"#;
+
+        let outcomes: Vec<Bet> = Outcome::from_f1_json(feed)
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                Bet::FinishPosition {
+                    driver: Driver::VER,
+                    position: Position::new(1),
+                },
+                Bet::FastestLap(Driver::VER),
+                Bet::FinishPosition {
+                    driver: Driver::LEC,
+                    position: Position::new(2),
+                },
+                Bet::DoesNotFinish(Driver::ALB),
+            ]
+        );
+    }
+    #[test]
+    fn feed_outcomes_pay_the_configured_rewards() {
+        let feed = r#"[
+            { "driver": "VER", "position": 1, "fastestLap": true },
+            { "driver": "ALB", "dnf": true }
+        ]"#;
+        let config = PoolConfig {
+            feed_position_reward: 25,
+            feed_fastest_lap_reward: 5,
+            feed_retirement_reward: 10,
+            ..Default::default()
+        };
+
+        let rewards: Vec<u64> = Outcome::from_f1_json_with(feed, &config)
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.reward)
+            .collect();
+        assert_eq!(rewards, vec![25, 5, 10]);
+        let rewards: Vec<u64> = Outcome::from_f1_json(feed)
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.reward)
+            .collect();
+        assert_eq!(rewards, vec![100, 50, 50]);
+    }
+    #[test]
+    fn the_failing_record_is_reported() {
+        let feed = r#"[{ "driver": "VER", "position": 1 }, { "driver": "XYZ", "position": 2 }]"#;
+
+        assert_eq!(
+            Outcome::from_f1_json(feed),
+            Err(ImportError::InvalidRecord {
+                index: 1,
+                reason: "XYZ is not a known driver code".into(),
+            })
+        );
+        assert_eq!(Outcome::from_f1_json("{}"), Err(ImportError::NotAFeed));
+    }
+    #[test]
+    fn contradicting_records_are_rejected() {
+        let invalid = |feed: &str| match Outcome::from_f1_json(feed) {
+            Err(ImportError::InvalidRecord { index, .. }) => Some(index),
+            _ => None,
+        };
+
+        assert_eq!(
+            invalid(r#"[{ "driver": "VER", "position": 1, "dnf": true }]"#),
+            Some(0)
+        );
+        assert_eq!(
+            invalid(r#"[{ "driver": "VER", "position": 1 }, { "driver": "VER", "position": 2 }]"#),
+            Some(1)
+        );
+        assert_eq!(
+            invalid(r#"[{ "driver": "VER", "position": 1 }, { "driver": "LEC", "position": 1 }]"#),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use super::{
+    errors::{ScoringError, ScoringOverflow},
+    Bet, BettingTable, PARTS_PER_POINT,
+};
+use crate::player::Player;
+
+/// The diversification penalty counts in millionths
+pub(super) const PENALTY_PARTS: u128 = 1_000_000;
+
+/// A single reward granted to a player, explaining where the results come from
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LedgerEntry {
+    pub player: Player,
+    pub bet: Bet,
+    /// Whole points granted, multiplier included
+    pub amount: u64,
+    /// The outcome paying the reward, None for bets settled by
+    /// a combination of outcomes
+    pub outcome_index: Option<usize>,
+}
+
+impl BettingTable {
+    /// Every reward granted so far, ordered by the outcome paying it.
+    /// Built from the current bets and outcomes, so it always adds up to the results
+    /// as long as every winning bet counts, no winnings are capped, no custom bets won
+    /// and no banker lost.
+    /// Stays empty as long as the pool is not active.
+    /// Panics when a reward overflows, see `try_ledger` to handle that instead
+    pub fn ledger(&self) -> Vec<LedgerEntry> {
+        self.try_ledger().expect("Rewards fit in a u64")
+    }
+    /// Every reward granted so far, or an error when a reward no longer fits in a u64
+    /// or the diversification penalty is invalid
+    pub fn try_ledger(&self) -> Result<Vec<LedgerEntry>, ScoringError> {
+        let mut ledger = Vec::new();
+        if !self.is_active() {
+            return Ok(ledger);
+        }
+
+        for (player, bets) in self.slips() {
+            for bet in bets {
+                let factor = u128::from(player.multiplier.value())
+                    .saturating_mul(self.diversification_factor(bet, bets)?);
+                for (outcome_index, reward) in self.payouts(bet) {
+                    // apply the multiplier first, only then round to whole points
+                    let amount = self.config.rounding.apply(
+                        reward.saturating_mul(factor),
+                        PARTS_PER_POINT * PENALTY_PARTS,
+                    );
+                    // a saturated reward is far beyond a u64, so it is caught here
+                    let amount = u64::try_from(amount).map_err(|_| ScoringOverflow {
+                        player: player.clone(),
+                    })?;
+                    ledger.push(LedgerEntry {
+                        player: player.clone(),
+                        bet: bet.clone(),
+                        amount,
+                        outcome_index,
+                    });
+                }
+            }
+        }
+        ledger.sort_by_key(|entry| {
+            (
+                entry.outcome_index.is_none(),
+                entry.outcome_index,
+                entry.player.name.clone(),
+            )
+        });
+
+        Ok(ledger)
+    }
+    /// The diversification penalty on a bet when the slip has too many bets
+    /// on its driver, in millionths, so `PENALTY_PARTS` when the bet is not penalized
+    pub(super) fn diversification_factor(
+        &self,
+        bet: &Bet,
+        slip: &[Bet],
+    ) -> Result<u128, ScoringError> {
+        let (Some(penalty), Some(driver)) = (self.config.diversification_penalty, bet.driver())
+        else {
+            return Ok(PENALTY_PARTS);
+        };
+        if !penalty.is_finite() || penalty < 0.0 {
+            return Err(ScoringError::InvalidPenalty(penalty));
+        }
+        let bets_on_driver = slip
+            .iter()
+            .filter(|other| other.driver() == Some(driver))
+            .count();
+
+        Ok(if bets_on_driver > self.config.max_bets_per_driver {
+            (penalty * PENALTY_PARTS as f64).round() as u128
+        } else {
+            PENALTY_PARTS
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LedgerEntry;
+    use crate::{
+        bets::{config::PoolConfig, errors::ScoringError, Bet, BettingTable, Outcome},
+        player::{Multiplier, Player},
+        race::Position,
+        teams::Driver,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn concentrating_on_a_single_driver_is_penalized() {
+        let score_with = |diversification_penalty: Option<f64>| {
+            let mut betting_table = BettingTable::with_config(PoolConfig {
+                diversification_penalty,
+                ..Default::default()
+            });
+            let player = Player::create("Nuyts".into(), None);
+            for bet in [
+                Bet::FastestLap(Driver::VER),
+                Bet::PolePosition(Driver::VER),
+                Bet::DriverOfTheDay(Driver::VER),
+                Bet::FirstRetirement(Driver::VER),
+                Bet::WillHaveSafetyCar(true),
+            ] {
+                assert!(betting_table.place(bet.clone(), &player).is_ok());
+                betting_table.register_outcome(Outcome {
+                    outcome: bet,
+                    reward: 100,
+                    registered_at: None,
+                });
+            }
+            betting_table.results().get(&player).copied()
+        };
+
+        assert_eq!(score_with(None), Some(500));
+        assert_eq!(score_with(Some(0.5)), Some(300));
+    }
+    #[test]
+    fn invalid_penalties_are_an_error() {
+        for penalty in [f64::NAN, -0.5, f64::INFINITY] {
+            let mut betting_table = BettingTable::with_config(PoolConfig {
+                diversification_penalty: Some(penalty),
+                ..Default::default()
+            });
+            let player = Player::create("Nuyts".into(), None);
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::VER), &player)
+                .is_ok());
+
+            assert!(matches!(
+                betting_table.try_results(),
+                Err(ScoringError::InvalidPenalty(_))
+            ));
+        }
+    }
+    #[test]
+    fn ledger_adds_up_to_the_results() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), Some(Multiplier::X2));
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+
+        for player in [&michiel, &demi] {
+            assert!(betting_table
+                .place(winning_position.clone(), player)
+                .is_ok());
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::LEC), player)
+                .is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::VER, Driver::LEC), &demi)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 500,
+            registered_at: None,
+        });
+        for outcome in Outcome::from_classification(&[Driver::VER, Driver::LEC], 10) {
+            betting_table.register_outcome(outcome);
+        }
+
+        let ledger = betting_table.ledger();
+        assert_eq!(ledger.len(), 5);
+        assert_eq!(
+            ledger[0],
+            LedgerEntry {
+                player: demi.clone(),
+                bet: Bet::FastestLap(Driver::LEC),
+                amount: 1000,
+                outcome_index: Some(0),
+            }
+        );
+
+        let mut totals = HashMap::<Player, u64>::new();
+        for entry in ledger {
+            *totals.entry(entry.player).or_insert(0) += entry.amount;
+        }
+        assert_eq!(totals, betting_table.results());
+        assert_eq!(totals.get(&demi).unwrap(), &(2 * (500 + 200 + 200)));
+    }
+}
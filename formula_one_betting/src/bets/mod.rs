@@ -1,14 +1,60 @@
+mod audit;
+mod config;
+mod custom;
+mod diff;
 mod errors;
+#[cfg(feature = "persistence")]
+mod finalize;
+#[cfg(feature = "import")]
+mod import;
+mod ledger;
+#[cfg(feature = "persistence")]
+mod persistence;
+mod rules;
+mod schema;
+mod slip;
+mod stats;
 
-use self::errors::ClashesWithExistingBet;
-use crate::{player::Player, race::Position, teams::Driver};
-use std::{collections::HashMap, mem::discriminant};
+use self::{
+    config::{PoolConfig, ScoringMode},
+    custom::CustomBet,
+    errors::{BetError, ClashesWithExistingBet, OutcomeNotFound, ScoringError, ScoringOverflow},
+    ledger::PENALTY_PARTS,
+    rules::MatchKind,
+};
+use crate::{
+    player::{Player, PlayerId, PlayerInterner},
+    race::{DriverResult, DriverStatus, Location, Position, Race},
+    teams::{Constructor, Driver, DriverSet},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Bumped whenever stored tables can no longer be read back, e.g. when the variants
+/// of `Bet` or the fields of the table change
+const SCHEMA_VERSION: u32 = 8;
+
+/// The version of the stored table format, e.g. for clients to drop stale caches
+pub fn schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// Rewards are counted in this many parts of a point, so scores stay whole numbers
+/// until they are rounded: it divides by every share an ExactDNFs reward is split into,
+/// half the reward over up to 20 drivers
+const PARTS_PER_POINT: u128 = 465_585_120;
 
 /// Possible things a player can bet on
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Bet {
     /// At which position does a driver finish the race
     FinishPosition { driver: Driver, position: Position },
+    /// From which grid slot does a driver start the race, after qualifying and penalties
+    StartPosition { driver: Driver, position: Position },
     /// Which driver does not finish the race
     DoesNotFinish(Driver),
     /// Which driver has the fastest lap at end of race
@@ -17,294 +63,4417 @@ pub enum Bet {
     DriverOfTheDay(Driver),
     /// Will there be a Safety Car?
     WillHaveSafetyCar(bool),
+    /// How many cars are classified at the end of the race
+    FinishersCount(u8),
+    /// Exactly which drivers do not finish the race, predicting at least one
+    ExactDNFs(DriverSet),
+    /// Both drivers are classified at the end of the race
+    BothFinish(Driver, Driver),
+    /// Which driver starts the race from pole position
+    PolePosition(Driver),
+    /// The driver finishes ahead of whoever started from pole,
+    /// never wins when the driver is the pole sitter
+    BeatsPoleSitter(Driver),
+    /// Which driver is the first to retire from the race
+    FirstRetirement(Driver),
+    /// Which rookie finishes highest, only drivers in their first season qualify
+    BestRookie(Driver),
+    /// Both drivers of the team finish first and second, in either order
+    ConstructorOneTwo(Constructor),
+    /// The team whose best placed driver finishes highest in the points, the top 10
+    FirstTeamToScore(Constructor),
+    /// Every leg has to win, pays the sum of the legs multiplied by the number of legs.
+    /// Needs at least two legs that could all win together, parlays can't be nested
+    Parlay(Vec<Bet>),
+    /// Pays what the consequent earns, but only when the condition wins as well.
+    /// Neither side can be a parlay or another conditional bet
+    Conditional {
+        if_bet: Box<Bet>,
+        then_bet: Box<Bet>,
+    },
+    /// The bet loses, paid once an outcome decided it.
+    /// Only bets settled by a single outcome can be negated
+    Not(Box<Bet>),
+    /// Will the fastest lap of the race beat the lap record of the circuit?
+    BreaksTrackRecord(bool),
+    /// How many time penalties the stewards hand out during the race
+    NumberOfPenalties(u8),
+    /// The driver gets at least one time penalty during the race
+    AnyDriverPenalized(Driver),
+    /// The driver finishes in the points, the top 10
+    ScoresPoints(Driver),
+    /// Will race control declare the race wet?
+    RaceIsWet(bool),
+    /// On which lap the first safety car is deployed
+    FirstSafetyCarLap(u8),
+    /// How many overtakes there are in the whole race
+    OvertakeCount(u32),
+    /// How many pit stops the driver makes during the race
+    PitStops { driver: Driver, count: u8 },
+}
+
+/// The part of a race weekend a bet belongs to, e.g. to group a slip in a UI
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum BetCategory {
+    Race,
+    Qualifying,
+    Sprint,
+    Novelty,
+}
+
+/// How unlikely a bet is to win, used to reward daring players
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RiskTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskTier {
+    /// The weight of a tier in the risk score of a slip: 1, 3 or 5
+    pub fn weight(&self) -> u32 {
+        match self {
+            RiskTier::Low => 1,
+            RiskTier::Medium => 3,
+            RiskTier::High => 5,
+        }
+    }
+}
+
+impl Bet {
+    /// Whether this bet is won by the given outcome
+    pub fn is_settled_by(&self, outcome: &Outcome) -> bool {
+        self.settles_against(outcome) == Some(MatchKind::Exact)
+    }
+    pub fn category(&self) -> BetCategory {
+        match self {
+            Bet::FinishPosition { .. }
+            | Bet::DoesNotFinish(_)
+            | Bet::FastestLap(_)
+            | Bet::FinishersCount(_)
+            | Bet::ExactDNFs(_)
+            | Bet::BothFinish(_, _)
+            | Bet::BeatsPoleSitter(_)
+            | Bet::FirstRetirement(_)
+            | Bet::BestRookie(_)
+            | Bet::ConstructorOneTwo(_)
+            | Bet::FirstTeamToScore(_)
+            | Bet::ScoresPoints(_)
+            | Bet::PitStops { .. }
+            | Bet::Parlay(_) => BetCategory::Race,
+            Bet::PolePosition(_) | Bet::StartPosition { .. } => BetCategory::Qualifying,
+            Bet::Conditional { then_bet, .. } => then_bet.category(),
+            Bet::Not(bet) => bet.category(),
+            Bet::DriverOfTheDay(_)
+            | Bet::WillHaveSafetyCar(_)
+            | Bet::BreaksTrackRecord(_)
+            | Bet::NumberOfPenalties(_)
+            | Bet::AnyDriverPenalized(_)
+            | Bet::RaceIsWet(_)
+            | Bet::FirstSafetyCarLap(_)
+            | Bet::OvertakeCount(_) => BetCategory::Novelty,
+        }
+    }
+    /// Bets picking a single driver out of the whole field are riskier than
+    /// bets that hold for most races, exact predictions and parlays are the riskiest
+    pub fn risk_tier(&self) -> RiskTier {
+        match self {
+            Bet::FinishPosition { .. }
+            | Bet::StartPosition { .. }
+            | Bet::ExactDNFs(_)
+            | Bet::FirstRetirement(_)
+            | Bet::ConstructorOneTwo(_)
+            | Bet::FirstSafetyCarLap(_)
+            | Bet::OvertakeCount(_)
+            | Bet::PitStops { .. }
+            | Bet::Parlay(_)
+            | Bet::Conditional { .. } => RiskTier::High,
+            Bet::DoesNotFinish(_)
+            | Bet::FastestLap(_)
+            | Bet::DriverOfTheDay(_)
+            | Bet::PolePosition(_)
+            | Bet::BeatsPoleSitter(_)
+            | Bet::BestRookie(_)
+            | Bet::FirstTeamToScore(_)
+            | Bet::BreaksTrackRecord(_)
+            | Bet::NumberOfPenalties(_)
+            | Bet::AnyDriverPenalized(_) => RiskTier::Medium,
+            Bet::WillHaveSafetyCar(_)
+            | Bet::FinishersCount(_)
+            | Bet::BothFinish(_, _)
+            | Bet::ScoresPoints(_)
+            | Bet::RaceIsWet(_)
+            | Bet::Not(_) => RiskTier::Low,
+        }
+    }
+    /// The driver this bet is about, None for bets on several drivers
+    /// or on the race as a whole
+    pub fn driver(&self) -> Option<Driver> {
+        match self {
+            Bet::FinishPosition { driver, .. }
+            | Bet::StartPosition { driver, .. }
+            | Bet::PitStops { driver, .. }
+            | Bet::DoesNotFinish(driver)
+            | Bet::FastestLap(driver)
+            | Bet::DriverOfTheDay(driver)
+            | Bet::PolePosition(driver)
+            | Bet::BeatsPoleSitter(driver)
+            | Bet::FirstRetirement(driver)
+            | Bet::BestRookie(driver)
+            | Bet::AnyDriverPenalized(driver)
+            | Bet::ScoresPoints(driver) => Some(*driver),
+            Bet::Not(bet) => bet.driver(),
+            Bet::WillHaveSafetyCar(_)
+            | Bet::FinishersCount(_)
+            | Bet::ExactDNFs(_)
+            | Bet::BothFinish(_, _)
+            | Bet::ConstructorOneTwo(_)
+            | Bet::FirstTeamToScore(_)
+            | Bet::Parlay(_)
+            | Bet::Conditional { .. }
+            | Bet::BreaksTrackRecord(_)
+            | Bet::NumberOfPenalties(_)
+            | Bet::RaceIsWet(_)
+            | Bet::FirstSafetyCarLap(_)
+            | Bet::OvertakeCount(_) => None,
+        }
+    }
+    /// Every driver this bet refers to
+    pub fn drivers(&self) -> DriverSet {
+        match self {
+            Bet::ExactDNFs(drivers) => *drivers,
+            Bet::BothFinish(first, second) => [*first, *second].into_iter().collect(),
+            Bet::ConstructorOneTwo(team) | Bet::FirstTeamToScore(team) => {
+                let (first, second) = team.team().drivers();
+                [first, second].into_iter().collect()
+            }
+            Bet::Parlay(legs) => legs
+                .iter()
+                .flat_map(|leg| leg.drivers().drivers())
+                .collect(),
+            Bet::Conditional { if_bet, then_bet } => if_bet
+                .drivers()
+                .drivers()
+                .into_iter()
+                .chain(then_bet.drivers().drivers())
+                .collect(),
+            Bet::Not(bet) => bet.drivers(),
+            bet => bet.driver().into_iter().collect(),
+        }
+    }
+    /// The canonical form of a bet, so a pair of drivers
+    /// means the same bet regardless of the order they were given in
+    pub fn normalized(&self) -> Bet {
+        match self {
+            Bet::BothFinish(first, second) if (*second as u8) < (*first as u8) => {
+                Bet::BothFinish(*second, *first)
+            }
+            Bet::Parlay(legs) => Bet::Parlay(legs.iter().map(Bet::normalized).collect()),
+            Bet::Conditional { if_bet, then_bet } => Bet::Conditional {
+                if_bet: Box::new(if_bet.normalized()),
+                then_bet: Box::new(then_bet.normalized()),
+            },
+            Bet::Not(bet) => Bet::Not(Box::new(bet.normalized())),
+            bet => bet.clone(),
+        }
+    }
 }
 
 /// The current state or the eventual outcome of a certain bet
 /// Will be used to track live results on all matching bets
 /// and to calculate final winnings after the race
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct Outcome {
     outcome: Bet,
     reward: u64,
+    /// When the outcome was registered through `register_outcome_at`, in seconds
+    registered_at: Option<u64>,
 }
 
+impl Outcome {
+    /// When the outcome was registered, None when it was registered without a moment
+    pub fn registered_at(&self) -> Option<u64> {
+        self.registered_at
+    }
+    /// Whether both outcomes are about the same thing, whatever they reward
+    pub fn same_subject(&self, other: &Outcome) -> bool {
+        self.outcome == other.outcome
+    }
+    /// Turns the final classification, ordered from P1 onwards, into
+    /// a FinishPosition outcome for every finisher.
+    /// The reward scales with the position: P1 pays 20 times the base reward,
+    /// P20 pays the base reward.
+    /// Panics when the order is empty, longer than the grid or contains a driver twice,
+    /// or when the reward of P1 no longer fits in a u64
+    pub fn from_classification(order: &[Driver], base_reward: u64) -> Vec<Outcome> {
+        let has_duplicates = order
+            .iter()
+            .enumerate()
+            .any(|(index, driver)| order[..index].contains(driver));
+        if order.is_empty() || order.len() > 20 || has_duplicates {
+            panic!("Wrong input for classification");
+        }
+
+        order
+            .iter()
+            .enumerate()
+            .map(|(index, driver)| Outcome {
+                outcome: Bet::FinishPosition {
+                    driver: *driver,
+                    position: Position::new(index as u8 + 1),
+                },
+                reward: base_reward
+                    .checked_mul(20 - index as u64)
+                    .expect("Rewards fit in a u64"),
+                registered_at: None,
+            })
+            .collect()
+    }
+    /// Turns the end results of the drivers into outcomes, each paying the given reward.
+    /// A disqualified driver is not classified, so like a retirement
+    /// it settles DoesNotFinish bets. Finishers without a position are skipped
+    pub fn from_driver_results(results: &[DriverResult], reward: u64) -> Vec<Outcome> {
+        results
+            .iter()
+            .filter_map(|result| match (result.status, result.position) {
+                (DriverStatus::Finished, Some(position)) => Some(Bet::FinishPosition {
+                    driver: result.driver,
+                    position,
+                }),
+                (DriverStatus::Finished, None) => None,
+                (DriverStatus::DNF | DriverStatus::DSQ, _) => {
+                    Some(Bet::DoesNotFinish(result.driver))
+                }
+            })
+            .map(|outcome| Outcome {
+                outcome,
+                reward,
+                registered_at: None,
+            })
+            .collect()
+    }
+    /// Compares the fastest lap of the race, in seconds, with the lap record of the circuit.
+    /// None when the circuit has no record to beat
+    pub fn from_fastest_lap_time(
+        location: Location,
+        lap_time: f64,
+        reward: u64,
+    ) -> Option<Outcome> {
+        let record = location.lap_record()?;
+
+        Some(Outcome {
+            outcome: Bet::BreaksTrackRecord(lap_time < record),
+            reward,
+            registered_at: None,
+        })
+    }
+    /// Turns the time penalties of a race, one entry per penalty, into the penalty count
+    /// and an outcome for every driver penalized at least once, each paying the given reward.
+    /// The count saturates at 255 penalties, the most a NumberOfPenalties bet can call
+    pub fn from_penalties(penalized: &[Driver], reward: u64) -> Vec<Outcome> {
+        let drivers: DriverSet = penalized.iter().copied().collect();
+        let count = u8::try_from(penalized.len()).unwrap_or(u8::MAX);
+
+        std::iter::once(Bet::NumberOfPenalties(count))
+            .chain(drivers.drivers().into_iter().map(Bet::AnyDriverPenalized))
+            .map(|outcome| Outcome {
+                outcome,
+                reward,
+                registered_at: None,
+            })
+            .collect()
+    }
+}
+
+/// How players with an equal score are ordered on the leaderboard
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TieBreak {
+    /// Alphabetically by player name
+    Alphabetical,
+    /// The player who needed the fewest bets ranks first
+    FewestBets,
+}
+
+/// A player on the leaderboard with its rank, tied players share a rank
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RankedPlayer {
+    pub rank: usize,
+    pub player: Player,
+    pub score: u64,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct BettingTable {
-    /// The placed bets indexed by the playerName
-    placed_bets: HashMap<Player, Vec<Bet>>,
+    /// The format version the table is stored in, tables of any other version are rejected
+    #[cfg_attr(
+        feature = "persistence",
+        serde(deserialize_with = "persistence::current_schema_version")
+    )]
+    schema_version: u32,
+    /// Every player who placed a bet, so bets are stored by a compact id
+    players: PlayerInterner,
+    /// The placed bets indexed by the id of the player
+    placed_bets: HashMap<PlayerId, Vec<Bet>>,
     /// The eventual outcomes after/during a race
     outcomes: Vec<Outcome>,
+    /// The rules of this pool
+    config: PoolConfig,
+    /// The race bets are placed on, when known
+    race: Option<Race>,
+    /// When each player last placed a bet through `place_at`
+    last_placed_at: HashMap<PlayerId, u64>,
+    /// Every bet placed through `place_idempotent`, by the player and the key
+    placements_by_key: HashMap<(PlayerId, String), Bet>,
+    /// Where drivers are running during the race, only used for projections
+    running_positions: HashMap<Driver, Position>,
+    /// Stakes placed through `place_with_stake`, other bets cost the stake per bet
+    stakes: HashMap<(PlayerId, Bet), u64>,
+    /// Bets taken off the slips by `void_bets_matching`, indexed by the id of the player
+    voided_bets: HashMap<PlayerId, Vec<Bet>>,
+    /// Custom bets indexed by the id of the player, these are not stored with the table
+    #[cfg_attr(feature = "persistence", serde(skip))]
+    custom_bets: HashMap<PlayerId, Vec<Arc<dyn CustomBet>>>,
+    /// The bet every player nominated to win for the rest of their slip to count
+    bankers: HashMap<PlayerId, Bet>,
 }
 
 impl BettingTable {
     /// Create a new betting table
     pub fn new() -> Self {
+        Self::with_config(PoolConfig::default())
+    }
+    /// Create a new betting table following the given pool rules
+    pub fn with_config(config: PoolConfig) -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
+            players: PlayerInterner::default(),
             placed_bets: HashMap::new(),
             outcomes: Vec::new(),
+            config,
+            race: None,
+            last_placed_at: HashMap::new(),
+            placements_by_key: HashMap::new(),
+            running_positions: HashMap::new(),
+            stakes: HashMap::new(),
+            voided_bets: HashMap::new(),
+            custom_bets: HashMap::new(),
+            bankers: HashMap::new(),
+        }
+    }
+    /// Create a new betting table for a specific race,
+    /// finish positions are then checked against its grid
+    pub fn for_race(race: Race) -> Self {
+        Self {
+            race: Some(race),
+            ..Self::new()
         }
     }
+    /// Records where a driver is running during the race. Unlike a registered outcome
+    /// this never pays out, it only makes `projected_standings` count the finish
+    /// position bets the driver would win if the race ended now
+    pub fn update_running_position(&mut self, driver: Driver, position: Position) {
+        self.running_positions.insert(driver, position);
+    }
+    /// The race bets are placed on, e.g. to set its grid once qualifying is done
+    pub fn race_mut(&mut self) -> Option<&mut Race> {
+        self.race.as_mut()
+    }
     /// Registers something that happened in the race
     pub fn register_outcome(&mut self, outcome: Outcome) {
+        #[cfg(feature = "log")]
+        log::info!(
+            target: "formula_one_betting",
+            outcome:? = outcome.outcome,
+            reward = outcome.reward;
+            "registered an outcome"
+        );
         self.outcomes.push(outcome);
     }
-    /// Places a bet for a certain player
-    pub fn place(&mut self, bet: Bet, player: &Player) -> Result<Bet, ClashesWithExistingBet> {
-        if self.is_bet_valid(&bet, player) {
-            self.placed_bets
-                .entry(player.clone())
-                .or_insert_with(Vec::new)
-                .push(bet);
-
-            return Ok(bet);
+    /// Registers an outcome at a moment in time, in seconds, to replay the scores later on
+    pub fn register_outcome_at(&mut self, outcome: Outcome, now: u64) {
+        self.register_outcome(Outcome {
+            registered_at: Some(now),
+            ..outcome
+        });
+    }
+    /// Registers drivers classified in the same position, e.g. after a timing tie.
+    /// Every driver settles bets on that position with an equal share of the reward,
+    /// rounded down
+    pub fn register_dead_heat(&mut self, drivers: &[Driver], position: Position, reward: u64) {
+        let share = reward / drivers.len().max(1) as u64;
+        for driver in drivers {
+            self.register_outcome(Outcome {
+                outcome: Bet::FinishPosition {
+                    driver: *driver,
+                    position,
+                },
+                reward: share,
+                registered_at: None,
+            });
         }
-
-        Err(ClashesWithExistingBet { existing_bet: bet })
     }
-    /// Get the current results, based on current bets and outcomes
-    pub fn results(&self) -> HashMap<Player, u64> {
-        let mut scores = HashMap::<Player, u64>::new();
+    /// Strips a driver of their registered finish position after the race: the driver
+    /// counts as not finishing, like a disqualification in the driver results, and
+    /// everyone classified behind moves up a position, and a registered number of
+    /// finishers drops by one. Nothing changes for a driver without a registered finish
+    /// position. Every outcome keeps its reward, so a driver moving up does not take over
+    /// the reward of the new position, and a fastest lap of the driver still stands
+    pub fn apply_disqualification(&mut self, driver: Driver) {
+        let Some(disqualified) = self.finish_position_of(driver) else {
+            return;
+        };
 
-        for outcome in &self.outcomes {
-            for (player, bets) in self.placed_bets.iter() {
-                for bet in bets {
-                    if bet == &outcome.outcome {
-                        *scores.entry(player.clone()).or_insert(0) += outcome.reward;
+        for outcome in &mut self.outcomes {
+            match outcome.outcome {
+                Bet::FinishPosition {
+                    driver: finisher, ..
+                } if finisher == driver => outcome.outcome = Bet::DoesNotFinish(driver),
+                Bet::FinishPosition {
+                    driver: finisher,
+                    position,
+                } if position > disqualified => {
+                    outcome.outcome = Bet::FinishPosition {
+                        driver: finisher,
+                        position: Position::new(position.value() - 1),
                     }
                 }
+                Bet::FinishersCount(count) => {
+                    outcome.outcome = Bet::FinishersCount(count.saturating_sub(1))
+                }
+                _ => {}
             }
         }
-        // apply multipliers on final scores
-        let scores = scores
-            .iter_mut()
-            .map(|(player, score)| (player.clone(), *score * player.multiplier))
-            .collect();
+    }
+    /// Settles every safety car bet at once, whichever side was picked.
+    /// Replaces any safety car outcome registered before, so it only pays once
+    pub fn resolve_safety_car(&mut self, occurred: bool, reward: u64) {
+        self.outcomes
+            .retain(|outcome| !matches!(outcome.outcome, Bet::WillHaveSafetyCar(_)));
+        self.register_outcome(Outcome {
+            outcome: Bet::WillHaveSafetyCar(occurred),
+            reward,
+            registered_at: None,
+        });
+    }
+    /// Registers the driver with the most fan votes as driver of the day.
+    /// A tie goes to the best finisher, drivers without a finish position come last
+    /// and are separated by their order on the roster
+    pub fn resolve_driver_of_the_day(
+        &mut self,
+        votes: HashMap<Driver, u32>,
+        reward: u64,
+    ) -> Option<Driver> {
+        let most_votes = *votes.values().max()?;
+        let roster = Driver::all();
+        let driver_of_the_day = votes
+            .into_iter()
+            .filter(|(_, count)| *count == most_votes)
+            .map(|(driver, _)| driver)
+            .min_by_key(|driver| {
+                let position = self.finish_position_of(*driver);
+                let roster_index = roster.iter().position(|listed| listed == driver);
+                (position.is_none(), position, roster_index)
+            })?;
+
+        self.register_outcome(Outcome {
+            outcome: Bet::DriverOfTheDay(driver_of_the_day),
+            reward,
+            registered_at: None,
+        });
 
-        scores
+        Some(driver_of_the_day)
     }
-    fn is_bet_valid(&self, bet_type: &Bet, player: &Player) -> bool {
-        let existing_bets = self.get_bets_for(player);
-        if existing_bets.is_empty() {
-            return true;
-        }
-
-        use Bet::*;
-        match bet_type {
-            FinishPosition { driver, position } => {
-                let is_driver_free = existing_bets.iter().any(|bet| match *bet {
-                    FinishPosition {
-                        driver: inner_driver,
-                        position: _,
-                    } => *driver != inner_driver,
-                    _ => true,
-                });
+    /// Removes a registered outcome, e.g. when the stewards reverse a decision,
+    /// so its reward no longer counts in the results
+    pub fn cancel_outcome(&mut self, outcome: &Outcome) -> Result<(), OutcomeNotFound> {
+        let index = self
+            .outcomes
+            .iter()
+            .position(|registered| registered == outcome)
+            .ok_or_else(|| OutcomeNotFound {
+                outcome: outcome.clone(),
+            })?;
+        self.outcomes.remove(index);
 
-                let is_position_free = existing_bets.iter().any(|bet| match *bet {
-                    FinishPosition {
-                        driver: _,
-                        position: inner_position,
-                    } => *position != inner_position,
-                    _ => true,
-                });
+        Ok(())
+    }
+    /// Places a bet for a certain player
+    pub fn place(&mut self, bet: Bet, player: &Player) -> Result<Bet, BetError> {
+        let bet = bet.normalized();
+        let result = self.check_placement(&bet, &self.get_bets_for(player));
+        #[cfg(feature = "log")]
+        log::info!(
+            target: "formula_one_betting",
+            player = player.name.as_str(),
+            bet:? = bet,
+            result:? = result;
+            "placed a bet"
+        );
+        result?;
+
+        let id = self.players.intern(player);
+        self.placed_bets.entry(id).or_default().push(bet.clone());
 
-                is_driver_free && is_position_free
+        Ok(bet)
+    }
+    /// Places a bet with a stake of its own, paid from the bankroll of the player.
+    /// Rejected when the stake is more than the player has left
+    pub fn place_with_stake(
+        &mut self,
+        bet: Bet,
+        player: &Player,
+        stake: u64,
+    ) -> Result<Bet, BetError> {
+        let remaining = self.remaining_bankroll(player);
+        if stake > remaining {
+            return Err(BetError::InsufficientBankroll(remaining));
+        }
+
+        let bet = self.place(bet, player)?;
+        let id = self.players.intern(player);
+        self.stakes.insert((id, bet.clone()), stake);
+
+        Ok(bet)
+    }
+    /// The starting bankroll minus the stakes of every bet the player placed
+    pub fn remaining_bankroll(&self, player: &Player) -> u64 {
+        let Some(id) = self.players.id_of(player) else {
+            return self.config.bankroll;
+        };
+        let staked: u64 = self
+            .get_bets_for(player)
+            .iter()
+            .map(|bet| self.stake_of(id, bet))
+            .sum();
+
+        self.config.bankroll.saturating_sub(staked)
+    }
+    /// The stake a player placed on a bet, the stake per bet unless placed with a stake of its own
+    fn stake_of(&self, id: PlayerId, bet: &Bet) -> u64 {
+        self.stakes
+            .get(&(id, bet.clone()))
+            .copied()
+            .unwrap_or(self.config.stake_per_bet)
+    }
+    /// Places a bet at a moment in time, in seconds, rejecting bets after the deadline
+    /// of their category and players who placed a bet less than the configured cooldown ago
+    pub fn place_at(&mut self, bet: Bet, player: &Player, now: u64) -> Result<Bet, BetError> {
+        let category = bet.category();
+        if let Some(deadline) = self.config.deadlines.get(&category) {
+            if now >= *deadline {
+                return Err(BetError::BettingClosed(category));
             }
-            DoesNotFinish(driver) => !existing_bets
-                .iter()
-                .any(|bet| bet == &DoesNotFinish(*driver)),
-            DriverOfTheDay(_driver) => !existing_bets
-                .iter()
-                .any(|bet| discriminant(bet) == discriminant(&DriverOfTheDay(Driver::MAG))),
-            FastestLap(_driver) => !existing_bets
-                .iter()
-                .any(|bet| discriminant(bet) == discriminant(&FastestLap(Driver::MAG))),
-            WillHaveSafetyCar(_val) => {
-                existing_bets
-                    .iter()
-                    .filter(|&bet| {
-                        bet == &WillHaveSafetyCar(true) || bet == &WillHaveSafetyCar(false)
-                    })
-                    .count()
-                    == 0
+        }
+        if let (Some(cooldown), Some(last_placed_at)) = (
+            self.config.placement_cooldown,
+            self.players
+                .id_of(player)
+                .and_then(|id| self.last_placed_at.get(&id)),
+        ) {
+            let retry_at = last_placed_at.saturating_add(cooldown);
+            if now < retry_at {
+                return Err(BetError::RateLimited(retry_at));
             }
         }
+
+        let bet = self.place(bet, player)?;
+        let id = self.players.intern(player);
+        self.last_placed_at.insert(id, now);
+
+        Ok(bet)
     }
-    fn get_bets_for(&self, player: &Player) -> Vec<Bet> {
-        self.placed_bets.get(player).unwrap_or(&vec![]).clone()
-    }
-}
+    /// Places a bet only the first time the player used the key, e.g. to survive retried requests.
+    /// A later call with the same key and bet returns the placed bet again, reusing the key
+    /// for another bet is an error. Failed placements don't use up the key, so they can be retried
+    pub fn place_idempotent(
+        &mut self,
+        bet: Bet,
+        player: &Player,
+        key: &str,
+    ) -> Result<Bet, BetError> {
+        let id = self.players.intern(player);
+        if let Some(placed) = self.placements_by_key.get(&(id, key.into())) {
+            if *placed != bet {
+                return Err(BetError::KeyReused(placed.clone()));
+            }
+            return Ok(bet);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::{Bet, BettingTable};
-    use crate::{
-        bets::{errors::ClashesWithExistingBet, Outcome, Player, Position},
-        teams::Driver,
-    };
+        let bet = self.place(bet, player)?;
+        self.placements_by_key.insert((id, key.into()), bet.clone());
 
-    #[test]
-    fn can_place_a_bet() {
-        let mut betting_table = BettingTable::new();
-        let player = Player::create("Nuyts".into(), None);
-        let bet = Bet::DoesNotFinish(Driver::ALB);
-        let result = betting_table.place(bet, &player);
+        Ok(bet)
+    }
+    /// Places every bet of one player for another player, e.g. to copy a friend's slip.
+    /// Bets the other player can't place next to their own bets are skipped,
+    /// returns how many bets were copied. Nothing is copied when any other bet
+    /// can't be placed, e.g. when it is beyond the grid
+    pub fn copy_slip(&mut self, from: &Player, to: &Player) -> Result<usize, BetError> {
+        // check the whole slip first, so a failing copy leaves the slip of the player as is
+        let mut slip = self.get_bets_for(to);
+        let mut copies = Vec::new();
+        for bet in self.get_bets_for(from) {
+            let bet = bet.normalized();
+            match self.check_placement(&bet, &slip) {
+                Ok(()) => {
+                    slip.push(bet.clone());
+                    copies.push(bet);
+                }
+                Err(BetError::ClashesWithExistingBet(_)) => continue,
+                Err(error) => return Err(error),
+            }
+        }
 
-        assert!(result.is_ok())
+        let copied = copies.len();
+        for bet in copies {
+            self.place(bet, to)?;
+        }
+
+        Ok(copied)
     }
-    #[test]
-    fn cannot_place_the_same_bet_more_than_once() {
-        let mut betting_table = BettingTable::new();
-        let player = Player::create("Nuyts".into(), None);
-        let bet = Bet::DoesNotFinish(Driver::ALB);
-        let result = betting_table.place(bet, &player);
-        // So far so good
-        assert!(result.is_ok());
-        // Place the same bet again
-        let result = betting_table.place(bet, &player);
-        assert_eq!(
-            result.unwrap_err(),
-            ClashesWithExistingBet { existing_bet: bet }
+    /// Removes a bet the player placed earlier
+    pub fn cancel(&mut self, bet: &Bet, player: &Player) -> Result<Bet, BetError> {
+        let result = self.remove_bet(bet, player);
+        #[cfg(feature = "log")]
+        log::info!(
+            target: "formula_one_betting",
+            player = player.name.as_str(),
+            bet:? = bet,
+            result:? = result;
+            "cancelled a bet"
         );
+
+        result
     }
-    #[test]
-    fn single_player_can_place_multiple_unique_bets() {
-        let mut betting_table = BettingTable::new();
-        let player = Player::create("Nuyts".into(), None);
-        let first_bet = Bet::DoesNotFinish(Driver::ALB);
+    fn remove_bet(&mut self, bet: &Bet, player: &Player) -> Result<Bet, BetError> {
+        let bet = &bet.normalized();
+        let bets = self
+            .players
+            .id_of(player)
+            .and_then(|id| self.placed_bets.get_mut(&id))
+            .ok_or(BetError::BetNotFound(bet.clone()))?;
+        let index = bets
+            .iter()
+            .position(|placed| placed == bet)
+            .ok_or(BetError::BetNotFound(bet.clone()))?;
+        let bet = bets.remove(index);
+        if let Some(id) = self.players.id_of(player) {
+            self.stakes.remove(&(id, bet.clone()));
+            if self.bankers.get(&id) == Some(&bet) {
+                self.bankers.remove(&id);
+            }
+        }
 
-        let result = betting_table.place(first_bet, &player);
-        assert!(result.is_ok());
+        Ok(bet)
+    }
+    /// Swaps a placed bet for a new one in one go, the new bet only has to be
+    /// valid against the other bets of the player.
+    /// Nothing changes when the old bet is missing or the new one clashes
+    pub fn replace(&mut self, old: &Bet, new: Bet, player: &Player) -> Result<Bet, BetError> {
+        let (old, new) = (&old.normalized(), new.normalized());
+        let mut remaining_bets = self.get_bets_for(player);
+        let index = remaining_bets
+            .iter()
+            .position(|placed| placed == old)
+            .ok_or(BetError::BetNotFound(old.clone()))?;
+        remaining_bets.remove(index);
 
-        let second_bet = Bet::DoesNotFinish(Driver::PER);
-        let result = betting_table.place(second_bet, &player);
-        assert!(result.is_ok());
+        self.check_placement(&new, &remaining_bets)?;
+
+        let id = self.players.id_of(player);
+        if let Some(bets) = id.and_then(|id| self.placed_bets.get_mut(&id)) {
+            bets[index] = new.clone();
+        }
+        // the stake of the old bet carries over to the new one
+        if let Some(id) = id {
+            if let Some(stake) = self.stakes.remove(&(id, old.clone())) {
+                self.stakes.insert((id, new.clone()), stake);
+            }
+            // the new bet was not nominated, so the banker is gone
+            if self.bankers.get(&id) == Some(old) {
+                self.bankers.remove(&id);
+            }
+        }
+
+        Ok(new)
     }
-    #[test]
-    fn single_player_can_only_bet_once_on_safety_car() {
-        let mut betting_table = BettingTable::new();
-        let player = Player::create("Nuyts".into(), None);
-        let first_bet = Bet::WillHaveSafetyCar(true);
+    /// Voids every placed bet the predicate holds for, e.g. when an abandoned race
+    /// can't settle them. Voided bets are taken off the slips, so they neither win
+    /// nor lose and their stakes go back to the bankroll
+    pub fn void_bets_matching(&mut self, predicate: impl Fn(&Bet) -> bool) {
+        for (id, bets) in &mut self.placed_bets {
+            let (voided, kept): (Vec<Bet>, Vec<Bet>) =
+                bets.drain(..).partition(|bet| predicate(bet));
+            *bets = kept;
+            if voided.is_empty() {
+                continue;
+            }
+            for bet in &voided {
+                self.stakes.remove(&(*id, bet.clone()));
+                if self.bankers.get(id) == Some(bet) {
+                    self.bankers.remove(id);
+                }
+            }
+            self.voided_bets.entry(*id).or_default().extend(voided);
+        }
+    }
+    /// Nominates a placed bet of the player as their banker: as long as it does not win,
+    /// the whole slip scores nothing. Replaces the banker nominated before
+    pub fn set_banker(&mut self, player: &Player, bet: &Bet) -> Result<(), BetError> {
+        let bet = bet.normalized();
+        if !self.get_bets_for(player).contains(&bet) {
+            return Err(BetError::BetNotFound(bet));
+        }
 
-        let result = betting_table.place(first_bet, &player);
-        assert!(result.is_ok());
+        let id = self.players.intern(player);
+        self.bankers.insert(id, bet);
 
-        let second_bet = Bet::WillHaveSafetyCar(false);
-        let result = betting_table.place(second_bet, &player);
-        assert!(result.is_err());
+        Ok(())
     }
-    #[test]
-    fn cannot_bet_on_multiple_finish_positions_for_the_same_driver() {
-        let mut betting_table = BettingTable::new();
-        let player = Player::create("Nuyts".into(), None);
-        let first_bet = Bet::FinishPosition {
-            driver: Driver::HAM,
-            position: Position::new(1),
+    /// The bets of the player that were voided, in the order they were voided
+    pub fn voided_bets_for(&self, player: &Player) -> Vec<Bet> {
+        self.players
+            .id_of(player)
+            .and_then(|id| self.voided_bets.get(&id))
+            .cloned()
+            .unwrap_or_default()
+    }
+    /// Removes players without any bets left, e.g. after cancelling them all,
+    /// together with their stakes, voided bets, banker and placement history.
+    /// Returns how many players were removed
+    pub fn prune_empty_players(&mut self) -> usize {
+        let player_count = self.player_count();
+        let is_empty = |id: &&PlayerId| {
+            !self.custom_bets.contains_key(id) && self.placed_bets.get(id).is_none_or(Vec::is_empty)
         };
+        let pruned: HashSet<PlayerId> = self
+            .placed_bets
+            .keys()
+            .chain(self.voided_bets.keys())
+            .chain(self.bankers.keys())
+            .chain(self.last_placed_at.keys())
+            .chain(self.stakes.keys().map(|(id, _)| id))
+            .filter(is_empty)
+            .copied()
+            .collect();
 
-        let result = betting_table.place(first_bet, &player);
-        assert!(result.is_ok());
+        for id in &pruned {
+            self.players.forget(*id);
+        }
+        self.placed_bets.retain(|id, _| !pruned.contains(id));
+        self.voided_bets.retain(|id, _| !pruned.contains(id));
+        self.bankers.retain(|id, _| !pruned.contains(id));
+        self.last_placed_at.retain(|id, _| !pruned.contains(id));
+        self.stakes.retain(|(id, _), _| !pruned.contains(id));
 
-        let second_bet = Bet::FinishPosition {
-            driver: Driver::HAM, // We already did a bet on HAM finishing first, not allowed
-            position: Position::new(2),
-        };
-        let result = betting_table.place(second_bet, &player);
-        assert!(result.is_err());
+        player_count - self.player_count()
     }
-    #[test]
-    fn cannot_bet_on_multiple_finish_positions() {
-        let mut betting_table = BettingTable::new();
-        let player = Player::create("Nuyts".into(), None);
-        let first_bet = Bet::FinishPosition {
-            driver: Driver::LEC,
-            position: Position::new(1),
-        };
+    /// How many players joined the pool by placing bets, custom bets included
+    pub fn player_count(&self) -> usize {
+        self.joined_players().count()
+    }
+    /// Whether enough players joined for the pool to start scoring
+    pub fn is_active(&self) -> bool {
+        self.player_count() >= self.config.min_players
+    }
+    /// Get the current results, based on current bets and outcomes.
+    /// Stays empty as long as the pool is not active
+    /// Panics when a score overflows, see `try_results` to handle that instead
+    pub fn results(&self) -> HashMap<Player, u64> {
+        self.try_results().expect("Scores fit in a u64")
+    }
+    /// The current results, or an error instead of wrapping around when the score
+    /// of a player no longer fits in a u64 or the diversification penalty is invalid
+    pub fn try_results(&self) -> Result<HashMap<Player, u64>, ScoringError> {
+        let mut scores = self.uncapped_results()?;
+        self.wipe_out_lost_bankers(&mut scores, &HashMap::new());
+        self.cap_winnings(&mut scores);
 
-        let result = betting_table.place(first_bet, &player);
-        assert!(result.is_ok());
+        Ok(scores)
+    }
+    /// The score of a player when only the outcomes registered up to the given moment,
+    /// in seconds, were known. Only outcomes registered through `register_outcome_at`
+    /// have a moment, every other outcome can't be placed in time and never counts.
+    /// Panics when a score overflows, see `try_score_at` to handle that instead
+    pub fn score_at(&self, player: &Player, timestamp: u64) -> u64 {
+        self.try_score_at(player, timestamp)
+            .expect("Scores fit in a u64")
+    }
+    /// The score of a player at the given moment, or an error when a score
+    /// no longer fits in a u64
+    pub fn try_score_at(&self, player: &Player, timestamp: u64) -> Result<u64, ScoringError> {
+        let mut earlier = self.clone();
+        earlier.outcomes.retain(|outcome| {
+            outcome
+                .registered_at
+                .is_some_and(|registered_at| registered_at <= timestamp)
+        });
 
-        let second_bet = Bet::FinishPosition {
-            driver: Driver::HAM,
-            position: Position::new(1), // Already placed bet on LEC for position 1
+        Ok(earlier.try_results()?.get(player).copied().unwrap_or(0))
+    }
+    /// The results with the winnings above the cap shared by the players below it,
+    /// in proportion to their score. Points lost to rounding go to the players with
+    /// the largest remainders, so the total stays the same. A share that would take
+    /// a player above the cap is cut off there, the rest is shared again by the others.
+    /// The excess is only lost when every scoring player hit the cap.
+    /// Panics when a score overflows, see `try_redistribute_forfeited` to handle that instead
+    pub fn redistribute_forfeited(&self) -> HashMap<Player, u64> {
+        self.try_redistribute_forfeited()
+            .expect("Scores fit in a u64")
+    }
+    /// The results with the forfeited winnings shared, or an error when a score
+    /// no longer fits in a u64
+    pub fn try_redistribute_forfeited(&self) -> Result<HashMap<Player, u64>, ScoringError> {
+        // winnings lost to a banker are gone, they are not forfeited to the others
+        let mut uncapped = self.uncapped_results()?;
+        self.wipe_out_lost_bankers(&mut uncapped, &HashMap::new());
+        let mut scores = uncapped.clone();
+        self.cap_winnings(&mut scores);
+        let Some(cap) = self.config.max_winnings else {
+            return Ok(scores);
         };
-        let result = betting_table.place(second_bet, &player);
-        assert!(result.is_err());
 
-        let third_bet = Bet::FinishPosition {
-            driver: Driver::HAM,
-            position: Position::new(2), // This is valid again
+        // totals over every player can pass a u64, that is fine as long as every score fits
+        let total = |scores: &HashMap<Player, u64>| {
+            scores
+                .values()
+                .map(|score| u128::from(*score))
+                .sum::<u128>()
         };
-        let result = betting_table.place(third_bet, &player);
-        assert!(result.is_ok());
-    }
-    #[test]
-    fn many_players_can_place_many_different_bets_and_scoring_is_correct() {
-        let mut betting_table = BettingTable::new();
-        let michiel = Player::create("michiel".into(), None);
-        let demi = Player::create("demi".into(), Some(2));
+        let mut forfeited = total(&uncapped) - total(&scores);
+        // shares follow the scores before sharing, in every round
+        let weights: Vec<(Player, u128)> = scores
+            .iter()
+            .filter(|(_, score)| **score > 0 && **score < cap)
+            .map(|(player, score)| (player.clone(), u128::from(*score)))
+            .collect();
 
-        let result = betting_table.place(
-            Bet::FinishPosition {
-                driver: Driver::VER,
-                position: Position::new(1),
-            },
-            &demi,
-        );
-        assert!(result.is_ok());
+        // every round either hands out everything or fills up at least one player
+        while forfeited > 0 {
+            let receivers: Vec<&(Player, u128)> = weights
+                .iter()
+                .filter(|(player, _)| scores[player] < cap)
+                .collect();
+            let receiving_total = receivers.iter().map(|(_, weight)| weight).sum::<u128>();
+            if receiving_total == 0 {
+                break;
+            }
 
-        let result = betting_table.place(
-            Bet::FinishPosition {
-                driver: Driver::VER,
-                position: Position::new(1),
-            },
-            &michiel,
-        );
-        assert!(result.is_ok());
+            let mut remainders = Vec::with_capacity(receivers.len());
+            let mut paid_in_full = 0;
+            let mut handed_out = 0;
+            for (player, weight) in receivers {
+                let share = forfeited
+                    .checked_mul(*weight)
+                    .ok_or_else(|| ScoringOverflow {
+                        player: player.clone(),
+                    })?;
+                let paid = share / receiving_total;
+                let received = scores.get_mut(player).expect("Receivers have a score");
+                let room = u128::from(cap - *received);
+                let given = paid.min(room);
+                *received += given as u64;
+                paid_in_full += paid;
+                handed_out += given;
+                if paid < room {
+                    remainders.push((share % receiving_total, player.clone()));
+                }
+            }
+            remainders.sort_by(|(remainder, player), (other_remainder, other_player)| {
+                other_remainder
+                    .cmp(remainder)
+                    .then(player.name.cmp(&other_player.name))
+            });
+            for (_, player) in remainders
+                .into_iter()
+                .take((forfeited - paid_in_full) as usize)
+            {
+                *scores.get_mut(&player).expect("Receivers have a score") += 1;
+                handed_out += 1;
+            }
+            forfeited -= handed_out;
+        }
 
-        let result = betting_table.place(
-            Bet::FinishPosition {
-                driver: Driver::HAM,
-                position: Position::new(1),
-            },
-            &demi,
-        );
-        assert!(result.is_err());
+        Ok(scores)
+    }
+    /// The results before any winnings are capped
+    fn uncapped_results(&self) -> Result<HashMap<Player, u64>, ScoringError> {
+        let mut scores = HashMap::<Player, u64>::new();
+        let add = |score: &mut u64, amount: u64, player: &Player| {
+            *score = score.checked_add(amount).ok_or_else(|| ScoringOverflow {
+                player: player.clone(),
+            })?;
+            Ok::<_, ScoringOverflow>(())
+        };
 
-        let result = betting_table.place(Bet::WillHaveSafetyCar(true), &demi);
-        assert!(result.is_ok());
-        let result = betting_table.place(Bet::WillHaveSafetyCar(false), &demi);
+        match self.config.scoring {
+            ScoringMode::Sum => {
+                for entry in self.try_ledger()? {
+                    let score = scores.entry(entry.player.clone()).or_insert(0);
+                    add(score, entry.amount, &entry.player)?;
+                }
+            }
+            ScoringMode::BestPerCategory => {
+                let mut per_bet = HashMap::<(Player, Bet), u64>::new();
+                for entry in self.try_ledger()? {
+                    let amount = per_bet
+                        .entry((entry.player.clone(), entry.bet))
+                        .or_insert(0);
+                    add(amount, entry.amount, &entry.player)?;
+                }
+                let mut best_per_category = HashMap::<(Player, BetCategory), u64>::new();
+                for ((player, bet), amount) in per_bet {
+                    let best = best_per_category
+                        .entry((player, bet.category()))
+                        .or_insert(0);
+                    *best = (*best).max(amount);
+                }
+                for ((player, _), amount) in best_per_category {
+                    let score = scores.entry(player.clone()).or_insert(0);
+                    add(score, amount, &player)?;
+                }
+            }
+        }
+        for (player, amount) in self.custom_winnings()? {
+            let score = scores.entry(player.clone()).or_insert(0);
+            add(score, amount, &player)?;
+        }
+
+        Ok(scores)
+    }
+    /// The results if the race ended now, where every bet that is not winning yet
+    /// pays out when it is assumed to hit. Bets without an assumption earn nothing extra,
+    /// except finish positions matching where the driver is running. A slip earns nothing
+    /// while its banker is not winning, unless the banker is assumed to hit as well.
+    /// Panics when a score overflows, see `try_projected_standings` to handle that instead
+    pub fn projected_standings(&self, assume: &HashMap<Bet, bool>) -> HashMap<Player, u64> {
+        self.try_projected_standings(assume)
+            .expect("Scores fit in a u64")
+    }
+    /// The results if the race ended now, or an error when a score no longer fits in a u64
+    pub fn try_projected_standings(
+        &self,
+        assume: &HashMap<Bet, bool>,
+    ) -> Result<HashMap<Player, u64>, ScoringError> {
+        if !self.is_active() {
+            return self.try_results();
+        }
+        // a banker assumed to hit keeps the slip alive, any other banker not winning yet
+        // wipes it out whatever else is assumed
+        let mut scores = self.uncapped_results()?;
+        self.wipe_out_lost_bankers(&mut scores, assume);
+
+        for (id, bets) in &self.placed_bets {
+            if self.banker_lost(*id, assume) {
+                continue;
+            }
+            let player = self.players.player(*id);
+            for bet in bets {
+                let hits = assume.get(bet).copied().unwrap_or_else(|| match bet {
+                    Bet::FinishPosition { driver, position } => {
+                        self.running_positions.get(driver) == Some(position)
+                    }
+                    _ => false,
+                });
+                if self.winnings(bet).is_some() || !hits {
+                    continue;
+                }
+                let amount = self.config.rounding.apply(
+                    self.projected_reward(bet)
+                        .saturating_mul(player.multiplier.value().into())
+                        .saturating_mul(self.diversification_factor(bet, bets)?),
+                    PARTS_PER_POINT * PENALTY_PARTS,
+                );
+                let score = scores.entry(player.clone()).or_insert(0);
+                *score = u64::try_from(amount)
+                    .ok()
+                    .and_then(|amount| score.checked_add(amount))
+                    .ok_or_else(|| ScoringOverflow {
+                        player: player.clone(),
+                    })?;
+            }
+        }
+        self.cap_winnings(&mut scores);
+
+        Ok(scores)
+    }
+    /// Whether the banker of the player is not winning, and not assumed to hit either
+    fn banker_lost(&self, id: PlayerId, assume: &HashMap<Bet, bool>) -> bool {
+        self.bankers.get(&id).is_some_and(|banker| {
+            self.winnings(banker).is_none() && assume.get(banker) != Some(&true)
+        })
+    }
+    /// Wipes out the score of every player whose banker is lost, see `banker_lost`
+    fn wipe_out_lost_bankers(
+        &self,
+        scores: &mut HashMap<Player, u64>,
+        assume: &HashMap<Bet, bool>,
+    ) {
+        for id in self.bankers.keys() {
+            if self.banker_lost(*id, assume) {
+                if let Some(score) = scores.get_mut(self.players.player(*id)) {
+                    *score = 0;
+                }
+            }
+        }
+    }
+    /// Clamps every score at the configured maximum winnings
+    fn cap_winnings(&self, scores: &mut HashMap<Player, u64>) {
+        if let Some(max_winnings) = self.config.max_winnings {
+            for score in scores.values_mut() {
+                *score = (*score).min(max_winnings);
+            }
+        }
+    }
+    /// How daring the slip of a player is, the sum of the risk weights of its bets
+    pub fn risk_score(&self, player: &Player) -> u32 {
+        self.get_bets_for(player)
+            .iter()
+            .map(|bet| bet.risk_tier().weight())
+            .sum()
+    }
+    /// The bets of a player in the given category
+    pub fn bets_in_category(&self, player: &Player, category: BetCategory) -> Vec<Bet> {
+        self.get_bets_for(player)
+            .into_iter()
+            .filter(|bet| bet.category() == category)
+            .collect()
+    }
+    /// Drivers the player did not place any bet on yet
+    pub fn unbet_drivers(&self, player: &Player) -> Vec<Driver> {
+        let backed: DriverSet = self
+            .get_bets_for(player)
+            .iter()
+            .flat_map(|bet| bet.drivers().drivers())
+            .collect();
+
+        Driver::all()
+            .into_iter()
+            .filter(|driver| !backed.contains(*driver))
+            .collect()
+    }
+    /// The most a single bet of every category is set to pay, before the multiplier,
+    /// e.g. to show what a pool pays up front. Bets paid by their outcome count at
+    /// the projected reward, parlays are left out and so are categories without bets
+    pub fn reward_table(&self) -> Vec<(BetCategory, u64)> {
+        let bets = Self::simple_bets();
+
+        [
+            BetCategory::Race,
+            BetCategory::Qualifying,
+            BetCategory::Sprint,
+            BetCategory::Novelty,
+        ]
+        .into_iter()
+        .filter_map(|category| {
+            bets.iter()
+                .filter(|bet| bet.category() == category)
+                .map(|bet| {
+                    u64::try_from(self.projected_reward(bet) / PARTS_PER_POINT).unwrap_or(u64::MAX)
+                })
+                .max()
+                .map(|reward| (category, reward))
+        })
+        .collect()
+    }
+    /// A readable summary of the bets of a player, e.g. to confirm them by mail,
+    /// with the multiplier and the current score.
+    /// Panics when a score overflows, see `try_player_report` to handle that instead
+    pub fn player_report(&self, player: &Player) -> String {
+        self.try_player_report(player).expect("Scores fit in a u64")
+    }
+    /// A readable summary of the bets of a player, or an error when a score
+    /// no longer fits in a u64
+    pub fn try_player_report(&self, player: &Player) -> Result<String, ScoringError> {
+        let bets = self.get_bets_for(player);
+        if bets.is_empty() {
+            return Ok(format!("{} placed no bets", player.name));
+        }
+
+        let mut report = format!(
+            "Bets of {} (x{}):\n",
+            player.name,
+            player.multiplier.value()
+        );
+        for bet in bets {
+            report.push_str(&format!("- {bet}\n"));
+        }
+        let score = self.try_results()?.get(player).copied().unwrap_or(0);
+        report.push_str(&format!("Score: {score}"));
+
+        Ok(report)
+    }
+    /// Every bet on the driver the player could still place, e.g. to guide a player
+    /// through a UI. Finish positions are limited to the positions still free
+    pub fn suggestions_for_driver(&self, player: &Player, driver: Driver) -> Vec<Bet> {
+        let existing_bets = self.get_bets_for(player);
+
+        Self::simple_bets()
+            .into_iter()
+            .filter(|bet| {
+                bet.drivers().contains(driver) && self.check_placement(bet, &existing_bets).is_ok()
+            })
+            .collect()
+    }
+    /// The categories in which the player can still place at least one bet,
+    /// e.g. to gray out categories in a UI
+    pub fn available_bet_kinds(&self, player: &Player) -> Vec<BetCategory> {
+        let existing_bets = self.get_bets_for(player);
+        let candidates = Self::simple_bets();
+
+        [
+            BetCategory::Race,
+            BetCategory::Qualifying,
+            BetCategory::Sprint,
+            BetCategory::Novelty,
+        ]
+        .into_iter()
+        .filter(|category| {
+            candidates.iter().any(|bet| {
+                bet.category() == *category && self.check_placement(bet, &existing_bets).is_ok()
+            })
+        })
+        .collect()
+    }
+    /// Every bet that is not built out of other bets, with every count
+    /// and driver combination a single bet of its kind can take
+    fn simple_bets() -> Vec<Bet> {
+        let drivers = Driver::all();
+        let mut bets = vec![
+            Bet::WillHaveSafetyCar(true),
+            Bet::WillHaveSafetyCar(false),
+            Bet::BreaksTrackRecord(true),
+            Bet::BreaksTrackRecord(false),
+        ];
+        bets.extend((0..=20).map(Bet::NumberOfPenalties));
+        bets.extend([Bet::RaceIsWet(true), Bet::RaceIsWet(false)]);
+        // up to the distance of the longest race on the calendar, Monaco
+        bets.extend((1..=78).map(Bet::FirstSafetyCarLap));
+        // even the busiest races stay around a hundred overtakes
+        bets.extend((0..=100).map(Bet::OvertakeCount));
+        bets.extend((0..=20).map(Bet::FinishersCount));
+        bets.extend(Constructor::all().map(Bet::ConstructorOneTwo));
+        bets.extend(Constructor::all().map(Bet::FirstTeamToScore));
+        for (index, driver) in drivers.into_iter().enumerate() {
+            for position in (1..=20).map(Position::new) {
+                bets.push(Bet::FinishPosition { driver, position });
+                bets.push(Bet::StartPosition { driver, position });
+            }
+            bets.extend((0..=5).map(|count| Bet::PitStops { driver, count }));
+            bets.extend([
+                Bet::DoesNotFinish(driver),
+                Bet::FastestLap(driver),
+                Bet::DriverOfTheDay(driver),
+                Bet::ExactDNFs([driver].into_iter().collect()),
+                Bet::PolePosition(driver),
+                Bet::BeatsPoleSitter(driver),
+                Bet::FirstRetirement(driver),
+                Bet::BestRookie(driver),
+                Bet::AnyDriverPenalized(driver),
+                Bet::ScoresPoints(driver),
+            ]);
+            bets.extend(
+                drivers[index + 1..]
+                    .iter()
+                    .map(|other| Bet::BothFinish(driver, *other)),
+            );
+        }
+
+        bets
+    }
+    /// Positions on the grid of the race the player did not place a FinishPosition bet on yet,
+    /// a full grid of 20 cars when the race is not known
+    pub fn unbet_positions(&self, player: &Player) -> Vec<Position> {
+        let claimed: Vec<Position> = self
+            .get_bets_for(player)
+            .iter()
+            .filter_map(|bet| match bet {
+                Bet::FinishPosition { position, .. } => Some(*position),
+                _ => None,
+            })
+            .collect();
+
+        let grid_size = self.race.as_ref().map_or(20, Race::grid_size);
+        (1..=grid_size)
+            .map(Position::new)
+            .filter(|position| !claimed.contains(position))
+            .collect()
+    }
+    /// Every placed bet referring to the given driver, ordered by player name
+    pub fn bets_on_driver(&self, driver: Driver) -> Vec<(Player, Bet)> {
+        let mut bets: Vec<(Player, Bet)> = self
+            .slips()
+            .flat_map(|(player, bets)| bets.iter().map(move |bet| (player.clone(), bet.clone())))
+            .filter(|(_, bet)| bet.drivers().contains(driver))
+            .collect();
+        bets.sort_by(|(player, _), (other, _)| player.name.cmp(&other.name));
+
+        bets
+    }
+    /// Every placed bet the outcome wins, ordered by player name,
+    /// e.g. to announce what a newly registered outcome settled
+    pub fn settled_by(&self, outcome: &Outcome) -> Vec<(Player, Bet)> {
+        let mut bets: Vec<(Player, Bet)> = self
+            .slips()
+            .flat_map(|(player, bets)| bets.iter().map(move |bet| (player.clone(), bet.clone())))
+            .filter(|(_, bet)| self.is_winning_match(bet, outcome))
+            .collect();
+        bets.sort_by(|(player, _), (other, _)| player.name.cmp(&other.name));
+
+        bets
+    }
+    /// Sums the current results of all players per supported team,
+    /// players without a favorite team are left out.
+    /// Panics when a score overflows, see `try_team_standings` to handle that instead
+    pub fn team_standings(&self) -> HashMap<Constructor, u64> {
+        self.try_team_standings().expect("Scores fit in a u64")
+    }
+    /// The summed results per supported team, or an error when a score
+    /// or the total of a team no longer fits in a u64
+    pub fn try_team_standings(&self) -> Result<HashMap<Constructor, u64>, ScoringError> {
+        let mut standings = HashMap::<Constructor, u64>::new();
+
+        for (player, score) in self.try_results()? {
+            if let Some(team) = player.favorite_team {
+                let total = standings.entry(team).or_insert(0);
+                *total = total
+                    .checked_add(score)
+                    .ok_or(ScoringError::TeamOverflow(team))?;
+            }
+        }
+
+        Ok(standings)
+    }
+    /// The current results ordered from the highest to the lowest score,
+    /// ties are broken alphabetically.
+    /// Panics when a score overflows, see `try_leaderboard` to handle that instead
+    pub fn leaderboard(&self) -> Vec<(Player, u64)> {
+        self.try_leaderboard().expect("Scores fit in a u64")
+    }
+    /// The current results ordered from the highest to the lowest score,
+    /// or an error when a score no longer fits in a u64
+    pub fn try_leaderboard(&self) -> Result<Vec<(Player, u64)>, ScoringError> {
+        self.try_leaderboard_with(TieBreak::Alphabetical)
+    }
+    /// The players sharing the highest score right now, ordered by name.
+    /// Empty as long as nobody scored.
+    /// Panics when a score overflows, see `try_current_leaders` to handle that instead
+    pub fn current_leaders(&self) -> Vec<Player> {
+        self.try_current_leaders().expect("Scores fit in a u64")
+    }
+    /// The players sharing the highest score right now,
+    /// or an error when a score no longer fits in a u64
+    pub fn try_current_leaders(&self) -> Result<Vec<Player>, ScoringError> {
+        let leaderboard = self.try_leaderboard()?;
+        let Some(&(_, top_score)) = leaderboard.first() else {
+            return Ok(Vec::new());
+        };
+        if top_score == 0 {
+            return Ok(Vec::new());
+        }
+
+        Ok(leaderboard
+            .into_iter()
+            .take_while(|(_, score)| *score == top_score)
+            .map(|(player, _)| player)
+            .collect())
+    }
+    /// How many times the lead changed hands while the outcomes were registered, in the
+    /// order they were registered. A shared lead leaves the lead with whoever held it,
+    /// taking the lead for the first time is not a change.
+    /// Panics when a score overflows, see `try_lead_changes` to handle that instead
+    pub fn lead_changes(&self) -> usize {
+        self.try_lead_changes().expect("Scores fit in a u64")
+    }
+    /// How many times the lead changed hands, or an error when a score
+    /// no longer fits in a u64 at any point of the replay
+    pub fn try_lead_changes(&self) -> Result<usize, ScoringError> {
+        let mut replay = self.clone();
+        replay.outcomes.clear();
+        let mut leader: Option<Player> = None;
+        let mut changes = 0;
+
+        for outcome in &self.outcomes {
+            replay.outcomes.push(outcome.clone());
+            let leaders = replay.try_current_leaders()?;
+            let [new_leader] = leaders.as_slice() else {
+                continue;
+            };
+            if leader.as_ref().is_some_and(|leader| leader != new_leader) {
+                changes += 1;
+            }
+            leader = Some(new_leader.clone());
+        }
+
+        Ok(changes)
+    }
+    /// How many more points the player needs to pass the current score of the target,
+    /// None when the player is already ahead.
+    /// Panics when a score overflows, see `try_bets_to_overtake` to handle that instead
+    pub fn bets_to_overtake(&self, player: &Player, target: &Player) -> Option<u64> {
+        self.try_bets_to_overtake(player, target)
+            .expect("Scores fit in a u64")
+    }
+    /// How many more points the player needs to pass the target, or an error when
+    /// a score, or the score needed to pass the target, no longer fits in a u64
+    pub fn try_bets_to_overtake(
+        &self,
+        player: &Player,
+        target: &Player,
+    ) -> Result<Option<u64>, ScoringError> {
+        let scores = self.try_results()?;
+        let score_of = |player: &Player| scores.get(player).copied().unwrap_or(0);
+        let (score, target_score) = (score_of(player), score_of(target));
+        if score > target_score {
+            return Ok(None);
+        }
+
+        let needed = (target_score - score)
+            .checked_add(1)
+            .ok_or_else(|| ScoringOverflow {
+                player: player.clone(),
+            })?;
+        Ok(Some(needed))
+    }
+    /// The players with a score closest to the score of the player, paired with how far
+    /// they are ahead of the player, negative when behind. Equally close rivals are
+    /// ordered by name.
+    /// Panics when a score overflows, see `try_nearest_rivals` to handle that instead
+    pub fn nearest_rivals(&self, player: &Player, n: usize) -> Vec<(Player, i64)> {
+        self.try_nearest_rivals(player, n)
+            .expect("Scores fit in an i64")
+    }
+    /// The players with a score closest to the score of the player, or an error when
+    /// a score no longer fits in an i64
+    pub fn try_nearest_rivals(
+        &self,
+        player: &Player,
+        n: usize,
+    ) -> Result<Vec<(Player, i64)>, ScoringError> {
+        let scores = self.try_results()?;
+        // both scores are at least 0, so their difference always fits as well
+        let score_of = |player: &Player| {
+            i64::try_from(scores.get(player).copied().unwrap_or(0)).map_err(|_| ScoringOverflow {
+                player: player.clone(),
+            })
+        };
+        let score = score_of(player)?;
+
+        let mut rivals = self
+            .joined_players()
+            .filter(|rival| *rival != player)
+            .map(|rival| Ok((rival.clone(), score_of(rival)? - score)))
+            .collect::<Result<Vec<(Player, i64)>, ScoringOverflow>>()?;
+        rivals.sort_by(|(rival, difference), (other, other_difference)| {
+            difference
+                .unsigned_abs()
+                .cmp(&other_difference.unsigned_abs())
+                .then_with(|| rival.name.cmp(&other.name))
+        });
+        rivals.truncate(n);
+
+        Ok(rivals)
+    }
+    /// The current results ordered from the highest to the lowest score,
+    /// ties are broken by the given policy.
+    /// Panics when a score overflows, see `try_leaderboard_with` to handle that instead
+    pub fn leaderboard_with(&self, tie_break: TieBreak) -> Vec<(Player, u64)> {
+        self.try_leaderboard_with(tie_break)
+            .expect("Scores fit in a u64")
+    }
+    /// The current results ordered by the given tie break policy,
+    /// or an error when a score no longer fits in a u64
+    pub fn try_leaderboard_with(
+        &self,
+        tie_break: TieBreak,
+    ) -> Result<Vec<(Player, u64)>, ScoringError> {
+        let mut leaderboard: Vec<(Player, u64)> = self.try_results()?.into_iter().collect();
+
+        leaderboard.sort_by(|(player, score), (other_player, other_score)| {
+            let by_name = player.name.cmp(&other_player.name);
+            let tie = match tie_break {
+                TieBreak::Alphabetical => by_name,
+                TieBreak::FewestBets => self
+                    .get_bets_for(player)
+                    .len()
+                    .cmp(&self.get_bets_for(other_player).len())
+                    .then(by_name),
+            };
+
+            other_score.cmp(score).then(tie)
+        });
+
+        Ok(leaderboard)
+    }
+    /// Every reward a single bet earns against the outcomes registered so far,
+    /// paired with the index of the outcome paying it, in parts of a point.
+    /// Bets settled by a combination of outcomes have no single paying outcome
+    fn payouts(&self, bet: &Bet) -> Vec<(Option<usize>, u128)> {
+        match bet {
+            Bet::ExactDNFs(_)
+            | Bet::BothFinish(_, _)
+            | Bet::BeatsPoleSitter(_)
+            | Bet::BestRookie(_)
+            | Bet::ConstructorOneTwo(_)
+            | Bet::FirstTeamToScore(_)
+            | Bet::ScoresPoints(_)
+            | Bet::StartPosition { .. }
+            | Bet::Parlay(_)
+            | Bet::Conditional { .. }
+            | Bet::Not(_) => self
+                .combined_winnings(bet)
+                .map(|reward| (None, reward))
+                .into_iter()
+                .collect(),
+            _ => {
+                let factor = self.reward_factor(bet);
+                self.outcomes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, outcome)| self.is_winning_match(bet, outcome))
+                    .map(|(index, outcome)| {
+                        (Some(index), points(outcome.reward).saturating_mul(factor))
+                    })
+                    .collect()
+            }
+        }
+    }
+    /// What a bet assumed to hit is projected to earn before the multiplier,
+    /// in parts of a point
+    fn projected_reward(&self, bet: &Bet) -> u128 {
+        match bet {
+            Bet::ExactDNFs(_) => points(self.config.exact_dnfs_reward),
+            Bet::BothFinish(_, _) => points(self.config.both_finish_reward),
+            Bet::BeatsPoleSitter(_) => points(self.config.beats_pole_sitter_reward),
+            Bet::BestRookie(_) => points(self.config.best_rookie_reward),
+            Bet::ConstructorOneTwo(_) => points(self.config.constructor_one_two_reward),
+            Bet::FirstTeamToScore(_) => points(self.config.first_team_to_score_reward),
+            Bet::ScoresPoints(_) => points(self.config.scores_points_reward),
+            Bet::StartPosition { .. } => points(self.config.start_position_reward),
+            Bet::Parlay(legs) => legs
+                .iter()
+                .map(|leg| self.projected_reward(leg))
+                .fold(0, u128::saturating_add)
+                .saturating_mul(legs.len() as u128),
+            Bet::Conditional { then_bet, .. } => self.projected_reward(then_bet),
+            Bet::Not(_) => points(self.config.negation_reward),
+            _ => points(self.config.projected_reward).saturating_mul(self.reward_factor(bet)),
+        }
+    }
+    /// How many times the reward of its outcome a bet pays, rarer calls pay more
+    fn reward_factor(&self, bet: &Bet) -> u128 {
+        match bet {
+            Bet::RaceIsWet(true) => self.config.wet_race_reward_factor.into(),
+            _ => 1,
+        }
+    }
+    /// What a single bet earns in total so far in parts of a point,
+    /// None when it is not winning
+    fn winnings(&self, bet: &Bet) -> Option<u128> {
+        let payouts = self.payouts(bet);
+
+        (!payouts.is_empty()).then(|| {
+            payouts
+                .iter()
+                .map(|(_, reward)| *reward)
+                .fold(0, u128::saturating_add)
+        })
+    }
+    /// What a bet settled by a combination of outcomes earns so far in parts of a point,
+    /// None when the bet is not winning.
+    /// Shares of a reward can leave a fraction of a point, the ledger decides how to round it
+    fn combined_winnings(&self, bet: &Bet) -> Option<u128> {
+        match bet {
+            // An exact match pays the full reward, otherwise every correctly
+            // predicted retirement pays an equal share of half the reward
+            Bet::ExactDNFs(predicted) => {
+                let retired = self.retired_drivers();
+                let reward = points(self.config.exact_dnfs_reward);
+                if *predicted == retired {
+                    return Some(reward);
+                }
+
+                // a share is a whole number of parts, see PARTS_PER_POINT
+                let correct = predicted.intersection(&retired).len() as u128;
+                (correct > 0).then(|| reward / (2 * predicted.len() as u128) * correct)
+            }
+            // Only pays once both drivers are classified
+            Bet::BothFinish(first, second) => {
+                let retired = self.retired_drivers();
+                let is_classified = |driver: &Driver| {
+                    !retired.contains(*driver) && self.finish_position_of(*driver).is_some()
+                };
+
+                (is_classified(first) && is_classified(second))
+                    .then_some(points(self.config.both_finish_reward))
+            }
+            // Pays once the driver is classified ahead of the pole sitter
+            Bet::BeatsPoleSitter(driver) => {
+                let pole_sitter =
+                    self.outcomes
+                        .iter()
+                        .find_map(|outcome| match outcome.outcome {
+                            Bet::PolePosition(pole_sitter) => Some(pole_sitter),
+                            _ => None,
+                        })?;
+                if pole_sitter == *driver || self.retired_drivers().contains(*driver) {
+                    return None;
+                }
+
+                let position = self.finish_position_of(*driver)?;
+                let beats_pole_sitter = self.retired_drivers().contains(pole_sitter)
+                    || self
+                        .finish_position_of(pole_sitter)
+                        .is_some_and(|pole_sitter_position| position < pole_sitter_position);
+
+                beats_pole_sitter.then_some(points(self.config.beats_pole_sitter_reward))
+            }
+            Bet::Parlay(legs) => {
+                let leg_rewards = legs
+                    .iter()
+                    .map(|leg| self.winnings(leg))
+                    .collect::<Option<Vec<u128>>>()?;
+
+                Some(
+                    leg_rewards
+                        .into_iter()
+                        .fold(0, u128::saturating_add)
+                        .saturating_mul(legs.len() as u128),
+                )
+            }
+            Bet::BestRookie(driver) => {
+                let season = self.race.as_ref()?.season();
+                let best_rookie = self
+                    .outcomes
+                    .iter()
+                    .filter_map(|outcome| match outcome.outcome {
+                        Bet::FinishPosition { driver, position } if driver.is_rookie(season) => {
+                            Some((position, driver))
+                        }
+                        _ => None,
+                    })
+                    .min_by_key(|(position, _)| *position)
+                    .map(|(_, driver)| driver)?;
+
+                (best_rookie == *driver).then_some(points(self.config.best_rookie_reward))
+            }
+            Bet::ConstructorOneTwo(team) => {
+                let (first, second) = team.team().drivers();
+                let mut positions = [
+                    self.finish_position_of(first)?.value(),
+                    self.finish_position_of(second)?.value(),
+                ];
+                positions.sort();
+
+                (positions == [1, 2]).then_some(points(self.config.constructor_one_two_reward))
+            }
+            Bet::FirstTeamToScore(team) => {
+                let first_to_score = self
+                    .outcomes
+                    .iter()
+                    .filter_map(|outcome| match outcome.outcome {
+                        Bet::FinishPosition { driver, position } if position.points_paying() => {
+                            Some((position, driver))
+                        }
+                        _ => None,
+                    })
+                    .min_by_key(|(position, _)| *position)
+                    .map(|(_, driver)| driver.team())?;
+
+                (first_to_score == *team).then_some(points(self.config.first_team_to_score_reward))
+            }
+            Bet::ScoresPoints(driver) => self
+                .finish_position_of(*driver)?
+                .points_paying()
+                .then_some(points(self.config.scores_points_reward)),
+            Bet::StartPosition { driver, position } => {
+                let start_position = self.race.as_ref()?.start_position_of(*driver)?;
+
+                (start_position == *position).then_some(points(self.config.start_position_reward))
+            }
+            Bet::Conditional { if_bet, then_bet } => {
+                self.winnings(if_bet)?;
+                self.winnings(then_bet)
+            }
+            Bet::Not(bet) => {
+                let is_decided = self
+                    .outcomes
+                    .iter()
+                    .any(|outcome| bet.is_decided_by(outcome));
+
+                (is_decided && self.winnings(bet).is_none())
+                    .then_some(points(self.config.negation_reward))
+            }
+            _ => None,
+        }
+    }
+    /// Drivers registered as not finishing the race
+    fn retired_drivers(&self) -> DriverSet {
+        self.outcomes
+            .iter()
+            .filter_map(|outcome| match outcome.outcome {
+                Bet::DoesNotFinish(driver) => Some(driver),
+                _ => None,
+            })
+            .collect()
+    }
+    /// Registered finish position of a driver
+    fn finish_position_of(&self, driver: Driver) -> Option<Position> {
+        self.outcomes
+            .iter()
+            .find_map(|outcome| match outcome.outcome {
+                Bet::FinishPosition {
+                    driver: finisher,
+                    position,
+                } if finisher == driver => Some(position),
+                _ => None,
+            })
+    }
+    /// Whether the outcome wins the bet, a count may be off within the pool tolerance
+    fn is_winning_match(&self, bet: &Bet, outcome: &Outcome) -> bool {
+        match bet.settles_against(outcome) {
+            Some(MatchKind::Exact) => true,
+            Some(MatchKind::Off(distance)) => match bet {
+                Bet::FirstSafetyCarLap(_) => {
+                    distance <= self.config.safety_car_lap_tolerance.into()
+                }
+                Bet::OvertakeCount(_) => distance <= self.config.overtake_count_tolerance,
+                Bet::PitStops { .. } => distance <= self.config.pit_stops_tolerance.into(),
+                _ => distance <= self.config.finishers_count_tolerance.into(),
+            },
+            None => false,
+        }
+    }
+    /// The leaderboard with standard competition ranking, e.g. 1, 2, 2, 4.
+    /// Panics when a score overflows, see `try_ranked` to handle that instead
+    pub fn ranked(&self) -> Vec<RankedPlayer> {
+        self.try_ranked().expect("Scores fit in a u64")
+    }
+    /// The ranked leaderboard, or an error when a score no longer fits in a u64
+    pub fn try_ranked(&self) -> Result<Vec<RankedPlayer>, ScoringError> {
+        let leaderboard = self.try_leaderboard()?;
+        let mut ranked = Vec::<RankedPlayer>::with_capacity(leaderboard.len());
+
+        for (index, (player, score)) in leaderboard.into_iter().enumerate() {
+            let rank = match ranked.last() {
+                Some(previous) if previous.score == score => previous.rank,
+                _ => index + 1,
+            };
+            ranked.push(RankedPlayer {
+                rank,
+                player,
+                score,
+            });
+        }
+
+        Ok(ranked)
+    }
+    fn check_placement(&self, bet: &Bet, existing_bets: &[Bet]) -> Result<(), BetError> {
+        if let (
+            Some(race),
+            Bet::FinishPosition { position, .. } | Bet::StartPosition { position, .. },
+        ) = (&self.race, bet)
+        {
+            if position.value() > race.grid_size() {
+                return Err(BetError::PositionOutOfGrid(*position));
+            }
+        }
+        if let Bet::BestRookie(driver) = bet {
+            let race = self.race.as_ref().ok_or(BetError::SeasonUnknown)?;
+            if !driver.is_rookie(race.season()) {
+                return Err(BetError::NotARookie(*driver));
+            }
+        }
+        if !self.is_valid_against(bet, existing_bets) {
+            return Err(ClashesWithExistingBet {
+                existing_bet: bet.clone(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+    /// Checks whether a bet can be added next to the existing bets of a player,
+    /// hedges only clash with the existing bets when the pool forbids hedging
+    fn is_valid_against(&self, bet_type: &Bet, existing_bets: &[Bet]) -> bool {
+        bet_type.is_well_formed()
+            && !existing_bets.iter().any(|bet| {
+                bet_type.clashes_with(bet) && !(self.config.allow_hedging && bet_type.hedges(bet))
+            })
+    }
+    fn get_bets_for(&self, player: &Player) -> Vec<Bet> {
+        self.players
+            .id_of(player)
+            .and_then(|id| self.placed_bets.get(&id))
+            .cloned()
+            .unwrap_or_default()
+    }
+    /// Every player who placed a bet or a custom bet
+    fn joined_players(&self) -> impl Iterator<Item = &Player> {
+        let custom_only = self
+            .custom_bets
+            .keys()
+            .filter(|id| !self.placed_bets.contains_key(id));
+
+        self.placed_bets
+            .keys()
+            .chain(custom_only)
+            .map(|id| self.players.player(*id))
+    }
+    /// Every slip together with the player who placed it
+    fn slips(&self) -> impl Iterator<Item = (&Player, &Vec<Bet>)> {
+        self.placed_bets
+            .iter()
+            .map(|(id, bets)| (self.players.player(*id), bets))
+    }
+}
+
+/// A whole number of points in parts of a point
+fn points(points: u64) -> u128 {
+    u128::from(points) * PARTS_PER_POINT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        config::{RoundingMode, ScoringMode},
+        Bet, BetCategory, BettingTable, PoolConfig, RankedPlayer, RiskTier, TieBreak,
+    };
+    use crate::{
+        bets::{
+            errors::{
+                BetError, ClashesWithExistingBet, OutcomeNotFound, ScoringError, ScoringOverflow,
+            },
+            Outcome, Player, Position,
+        },
+        player::Multiplier,
+        race::{DriverResult, DriverStatus, Location, Race},
+        teams::{Constructor, Driver, DriverSet},
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn can_place_a_bet() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let bet = Bet::DoesNotFinish(Driver::ALB);
+        let result = betting_table.place(bet, &player);
+
+        assert!(result.is_ok())
+    }
+    #[test]
+    fn cannot_place_the_same_bet_more_than_once() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let bet = Bet::DoesNotFinish(Driver::ALB);
+        let result = betting_table.place(bet.clone(), &player);
+        // So far so good
+        assert!(result.is_ok());
+        // Place the same bet again
+        let result = betting_table.place(bet.clone(), &player);
+        assert_eq!(
+            result.unwrap_err(),
+            BetError::ClashesWithExistingBet(ClashesWithExistingBet { existing_bet: bet })
+        );
+    }
+    #[test]
+    fn single_player_can_place_multiple_unique_bets() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let first_bet = Bet::DoesNotFinish(Driver::ALB);
+
+        let result = betting_table.place(first_bet, &player);
+        assert!(result.is_ok());
+
+        let second_bet = Bet::DoesNotFinish(Driver::PER);
+        let result = betting_table.place(second_bet, &player);
+        assert!(result.is_ok());
+    }
+    #[test]
+    fn single_player_can_only_bet_once_on_safety_car() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let first_bet = Bet::WillHaveSafetyCar(true);
+
+        let result = betting_table.place(first_bet, &player);
+        assert!(result.is_ok());
+
+        let second_bet = Bet::WillHaveSafetyCar(false);
+        let result = betting_table.place(second_bet, &player);
         assert!(result.is_err());
+    }
+    #[test]
+    fn cannot_bet_on_multiple_finish_positions_for_the_same_driver() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let first_bet = Bet::FinishPosition {
+            driver: Driver::HAM,
+            position: Position::new(1),
+        };
+
+        let result = betting_table.place(first_bet, &player);
+        assert!(result.is_ok());
+
+        let second_bet = Bet::FinishPosition {
+            driver: Driver::HAM, // We already did a bet on HAM finishing first, not allowed
+            position: Position::new(2),
+        };
+        let result = betting_table.place(second_bet, &player);
+        assert!(result.is_err());
+    }
+    #[test]
+    fn cannot_bet_on_multiple_finish_positions() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let first_bet = Bet::FinishPosition {
+            driver: Driver::LEC,
+            position: Position::new(1),
+        };
+
+        let result = betting_table.place(first_bet, &player);
+        assert!(result.is_ok());
+
+        let second_bet = Bet::FinishPosition {
+            driver: Driver::HAM,
+            position: Position::new(1), // Already placed bet on LEC for position 1
+        };
+        let result = betting_table.place(second_bet, &player);
+        assert!(result.is_err());
+
+        let third_bet = Bet::FinishPosition {
+            driver: Driver::HAM,
+            position: Position::new(2), // This is valid again
+        };
+        let result = betting_table.place(third_bet, &player);
+        assert!(result.is_ok());
+    }
+    #[test]
+    fn many_players_can_place_many_different_bets_and_scoring_is_correct() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), Some(Multiplier::X2));
+
+        let result = betting_table.place(
+            Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(1),
+            },
+            &demi,
+        );
+        assert!(result.is_ok());
+
+        let result = betting_table.place(
+            Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(1),
+            },
+            &michiel,
+        );
+        assert!(result.is_ok());
+
+        let result = betting_table.place(
+            Bet::FinishPosition {
+                driver: Driver::HAM,
+                position: Position::new(1),
+            },
+            &demi,
+        );
+        assert!(result.is_err());
+
+        let result = betting_table.place(Bet::WillHaveSafetyCar(true), &demi);
+        assert!(result.is_ok());
+        let result = betting_table.place(Bet::WillHaveSafetyCar(false), &demi);
+        assert!(result.is_err());
+
+        let result = betting_table.place(Bet::FastestLap(Driver::LEC), &demi);
+        assert!(result.is_ok());
+        let result = betting_table.place(Bet::FastestLap(Driver::HAM), &demi);
+        assert!(result.is_err());
+
+        let result = betting_table.place(Bet::DriverOfTheDay(Driver::LEC), &demi);
+        assert!(result.is_ok());
+        let result = betting_table.place(Bet::DriverOfTheDay(Driver::HAM), &demi);
+        assert!(result.is_err());
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(1),
+            },
+            reward: 1000,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::WillHaveSafetyCar(true),
+            reward: 500,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 2500,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::DriverOfTheDay(Driver::LEC),
+            reward: 5000,
+            registered_at: None,
+        });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&demi).unwrap(), &18000);
+        assert_eq!(scores.get(&michiel).unwrap(), &1000);
+    }
+    #[test]
+    fn team_standings_sum_scores_of_supporters() {
+        let mut betting_table = BettingTable::new();
+        let michiel =
+            Player::create("michiel".into(), None).with_favorite_team(Constructor::Ferrari);
+        let demi = Player::create("demi".into(), Some(Multiplier::X2))
+            .with_favorite_team(Constructor::Ferrari);
+        let neutral = Player::create("neutral".into(), None);
+
+        for player in [&michiel, &demi, &neutral] {
+            let result = betting_table.place(Bet::FastestLap(Driver::LEC), player);
+            assert!(result.is_ok());
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let standings = betting_table.team_standings();
+        assert_eq!(standings.get(&Constructor::Ferrari).unwrap(), &300);
+        assert_eq!(standings.len(), 1);
+    }
+    #[test]
+    fn classification_produces_an_outcome_per_finisher() {
+        use Driver::*;
+        let order = [
+            VER, LEC, PER, SAI, HAM, RUS, NOR, OCO, ALO, BOT, VET, RIC, GAS, MAG, STR, MSC, TSU,
+            ZHO, ALB, LAT,
+        ];
+        let outcomes = Outcome::from_classification(&order, 10);
+
+        assert_eq!(outcomes.len(), 20);
+        assert_eq!(
+            outcomes[0].outcome,
+            Bet::FinishPosition {
+                driver: VER,
+                position: Position::new(1),
+            }
+        );
+        assert_eq!(outcomes[0].reward, 200);
+        assert_eq!(outcomes[19].reward, 10);
+    }
+    #[test]
+    #[should_panic]
+    fn classification_cannot_contain_a_driver_twice() {
+        Outcome::from_classification(&[Driver::VER, Driver::VER], 10);
+    }
+    #[test]
+    fn can_cancel_a_placed_bet() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let bet = Bet::FastestLap(Driver::LEC);
+        assert!(betting_table.place(bet.clone(), &player).is_ok());
+
+        assert_eq!(betting_table.cancel(&bet, &player), Ok(bet.clone()));
+        assert_eq!(
+            betting_table.cancel(&bet, &player),
+            Err(BetError::BetNotFound(bet))
+        );
+    }
+    #[test]
+    fn can_replace_a_finish_position_for_the_same_driver() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let old_bet = Bet::FinishPosition {
+            driver: Driver::HAM,
+            position: Position::new(1),
+        };
+        assert!(betting_table.place(old_bet.clone(), &player).is_ok());
+
+        let new_bet = Bet::FinishPosition {
+            driver: Driver::HAM,
+            position: Position::new(2),
+        };
+        let result = betting_table.replace(&old_bet, new_bet.clone(), &player);
+        assert_eq!(result, Ok(new_bet.clone()));
+        assert_eq!(betting_table.get_bets_for(&player), vec![new_bet]);
+    }
+    #[test]
+    fn failing_replace_leaves_the_bets_untouched() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let fastest_lap = Bet::FastestLap(Driver::LEC);
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        assert!(betting_table.place(fastest_lap.clone(), &player).is_ok());
+        assert!(betting_table
+            .place(finish_position.clone(), &player)
+            .is_ok());
+
+        let clashing_bet = Bet::FinishPosition {
+            driver: Driver::LEC,
+            position: Position::new(1), // Already taken by VER
+        };
+        let result = betting_table.replace(&fastest_lap, clashing_bet.clone(), &player);
+        assert_eq!(
+            result,
+            Err(BetError::ClashesWithExistingBet(ClashesWithExistingBet {
+                existing_bet: clashing_bet.clone()
+            }))
+        );
+
+        let missing_bet = Bet::DriverOfTheDay(Driver::ALO);
+        let result = betting_table.replace(&missing_bet, clashing_bet, &player);
+        assert_eq!(result, Err(BetError::BetNotFound(missing_bet)));
+
+        assert_eq!(
+            betting_table.get_bets_for(&player),
+            vec![fastest_lap, finish_position]
+        );
+    }
+    #[test]
+    fn every_bet_type_is_settled_by_its_matching_outcome() {
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        let bets = [
+            finish_position.clone(),
+            Bet::DoesNotFinish(Driver::ALB),
+            Bet::FastestLap(Driver::LEC),
+            Bet::DriverOfTheDay(Driver::HAM),
+            Bet::WillHaveSafetyCar(true),
+        ];
+
+        for bet in bets {
+            let outcome = Outcome {
+                outcome: bet.clone(),
+                reward: 100,
+                registered_at: None,
+            };
+            assert!(bet.is_settled_by(&outcome));
+        }
+
+        let other_position = Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(2),
+            },
+            reward: 100,
+            registered_at: None,
+        };
+        assert!(!finish_position.is_settled_by(&other_position));
+        let no_safety_car = Outcome {
+            outcome: Bet::WillHaveSafetyCar(false),
+            reward: 100,
+            registered_at: None,
+        };
+        assert!(!Bet::WillHaveSafetyCar(true).is_settled_by(&no_safety_car));
+    }
+    #[test]
+    fn tie_break_policies_order_equal_scores_differently() {
+        let mut betting_table = BettingTable::new();
+        let anna = Player::create("anna".into(), None);
+        let bob = Player::create("bob".into(), None);
+
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &anna)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(false), &anna)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &bob)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let leaderboard = betting_table.leaderboard_with(TieBreak::Alphabetical);
+        assert_eq!(leaderboard, vec![(anna.clone(), 100), (bob.clone(), 100)]);
+
+        let leaderboard = betting_table.leaderboard_with(TieBreak::FewestBets);
+        assert_eq!(leaderboard, vec![(bob, 100), (anna, 100)]);
+    }
+    #[test]
+    fn finishers_count_pays_on_exact_match_and_one_bet_per_player() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let other_player = Player::create("demi".into(), None);
+
+        assert!(betting_table
+            .place(Bet::FinishersCount(18), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FinishersCount(17), &player)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::FinishersCount(17), &other_player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FinishersCount(21), &Player::create("x".into(), None))
+            .is_err());
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishersCount(18),
+            reward: 300,
+            registered_at: None,
+        });
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&player).unwrap(), &300);
+        assert_eq!(scores.get(&other_player), None);
+    }
+    #[test]
+    fn finishers_count_near_miss_pays_within_tolerance() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            finishers_count_tolerance: 1,
+            ..Default::default()
+        });
+        let near = Player::create("near".into(), None);
+        let far = Player::create("far".into(), None);
+
+        assert!(betting_table.place(Bet::FinishersCount(17), &near).is_ok());
+        assert!(betting_table.place(Bet::FinishersCount(16), &far).is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishersCount(18),
+            reward: 300,
+            registered_at: None,
+        });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&near).unwrap(), &300);
+        assert_eq!(scores.get(&far), None);
+    }
+    #[test]
+    fn results_are_withheld_until_enough_players_joined() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            min_players: 2,
+            ..Default::default()
+        });
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(!betting_table.is_active());
+        assert!(betting_table.results().is_empty());
+
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::HAM), &demi)
+            .is_ok());
+        assert!(betting_table.is_active());
+        assert_eq!(betting_table.results().get(&michiel).unwrap(), &100);
+    }
+    #[test]
+    fn exact_dnfs_pay_full_reward_on_exact_set_and_partial_otherwise() {
+        let mut betting_table = BettingTable::new();
+        let exact = Player::create("exact".into(), None);
+        let partial = Player::create("partial".into(), None);
+        let wrong = Player::create("wrong".into(), None);
+
+        let bet = |drivers: &[Driver]| Bet::ExactDNFs(drivers.iter().copied().collect());
+        assert!(betting_table
+            .place(bet(&[Driver::ALB, Driver::MSC]), &exact)
+            .is_ok());
+        assert!(betting_table.place(bet(&[Driver::ALB]), &exact).is_err());
+        assert!(betting_table
+            .place(bet(&[Driver::ALB, Driver::ZHO]), &partial)
+            .is_ok());
+        assert!(betting_table.place(bet(&[Driver::VER]), &wrong).is_ok());
+        assert!(betting_table
+            .place(Bet::ExactDNFs(DriverSet::default()), &wrong)
+            .is_err());
+
+        for driver in [Driver::ALB, Driver::MSC] {
+            betting_table.register_outcome(Outcome {
+                outcome: Bet::DoesNotFinish(driver),
+                reward: 100,
+                registered_at: None,
+            });
+        }
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&exact).unwrap(), &1000);
+        assert_eq!(scores.get(&partial).unwrap(), &250);
+        assert_eq!(scores.get(&wrong), None);
+    }
+    #[test]
+    fn both_finish_pair_order_does_not_matter() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+
+        let result = betting_table.place(Bet::BothFinish(Driver::HAM, Driver::VER), &player);
+        assert_eq!(result, Ok(Bet::BothFinish(Driver::VER, Driver::HAM)));
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::VER, Driver::HAM), &player)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::LEC, Driver::LEC), &player)
+            .is_err());
+    }
+    #[test]
+    fn both_finish_pays_when_neither_driver_retires() {
+        let mut betting_table = BettingTable::new();
+        let winner = Player::create("winner".into(), None);
+        let loser = Player::create("loser".into(), None);
+
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::VER, Driver::HAM), &winner)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::VER, Driver::ALB), &loser)
+            .is_ok());
+        for outcome in Outcome::from_classification(&[Driver::VER, Driver::HAM], 10) {
+            betting_table.register_outcome(outcome);
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::DoesNotFinish(Driver::ALB),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&winner).unwrap(), &200);
+        assert_eq!(scores.get(&loser), None);
+    }
+    #[test]
+    fn rounding_mode_collapses_fractional_scores() {
+        let score_with = |rounding: RoundingMode| {
+            let mut betting_table = BettingTable::with_config(PoolConfig {
+                rounding,
+                ..Default::default()
+            });
+            let player = Player::create("Nuyts".into(), Some(Multiplier::X2));
+            let dnfs = [Driver::ALB, Driver::MSC, Driver::ZHO]
+                .into_iter()
+                .collect();
+            assert!(betting_table.place(Bet::ExactDNFs(dnfs), &player).is_ok());
+            for driver in [Driver::ALB, Driver::MSC] {
+                betting_table.register_outcome(Outcome {
+                    outcome: Bet::DoesNotFinish(driver),
+                    reward: 100,
+                    registered_at: None,
+                });
+            }
+
+            // two sixths of the 1000 reward, times 2, is 666.67
+            *betting_table.results().get(&player).unwrap()
+        };
+
+        assert_eq!(score_with(RoundingMode::Floor), 666);
+        assert_eq!(score_with(RoundingMode::Ceil), 667);
+        assert_eq!(score_with(RoundingMode::Nearest), 667);
+    }
+    #[test]
+    fn bets_on_driver_only_lists_bets_referring_to_that_driver() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+
+        assert!(betting_table
+            .place(finish_position.clone(), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::VER, Driver::HAM), &demi)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &demi)
+            .is_ok());
+
+        assert_eq!(
+            betting_table.bets_on_driver(Driver::VER),
+            vec![
+                (demi, Bet::BothFinish(Driver::VER, Driver::HAM)),
+                (michiel, finish_position),
+            ]
+        );
+    }
+    #[cfg(feature = "arbitrary")]
+    proptest::proptest! {
+        #[test]
+        fn placing_and_scoring_arbitrary_bets_never_panics(
+            bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..512)
+        ) {
+            use arbitrary::{Arbitrary, Unstructured};
+
+            let mut unstructured = Unstructured::new(&bytes);
+            let mut betting_table = BettingTable::new();
+            let players = [
+                Player::create("michiel".into(), None),
+                Player::create("demi".into(), Some(Multiplier::X2)),
+            ];
+            while !unstructured.is_empty() {
+                let Ok(bet) = Bet::arbitrary(&mut unstructured) else {
+                    break;
+                };
+                let player = &players[unstructured.len() % players.len()];
+                let _ = betting_table.place(bet.clone(), player);
+                betting_table.register_outcome(Outcome { outcome: bet, reward: 10, registered_at: None });
+            }
+
+            betting_table.results();
+        }
+    }
+    #[test]
+    fn beats_pole_sitter_pays_when_finishing_ahead_of_pole() {
+        let mut betting_table = BettingTable::new();
+        let winner = Player::create("winner".into(), None);
+        let loser = Player::create("loser".into(), None);
+        let pole_sitter = Player::create("pole".into(), None);
+
+        assert!(betting_table
+            .place(Bet::BeatsPoleSitter(Driver::VER), &winner)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::BeatsPoleSitter(Driver::VER), &winner)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::BeatsPoleSitter(Driver::HAM), &loser)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::BeatsPoleSitter(Driver::LEC), &pole_sitter)
+            .is_ok());
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::PolePosition(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+        for outcome in Outcome::from_classification(&[Driver::VER, Driver::LEC, Driver::HAM], 10) {
+            betting_table.register_outcome(outcome);
+        }
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&winner).unwrap(), &300);
+        assert_eq!(scores.get(&loser), None);
+        assert_eq!(scores.get(&pole_sitter), None);
+    }
+    #[test]
+    fn pruning_removes_only_players_without_bets() {
+        let mut betting_table = BettingTable::new();
+        let active = Player::create("active".into(), None);
+        let idle = Player::create("idle".into(), None);
+        let bet = Bet::FastestLap(Driver::LEC);
+
+        assert!(betting_table.place(bet.clone(), &active).is_ok());
+        assert!(betting_table.place(bet.clone(), &idle).is_ok());
+        assert!(betting_table.cancel(&bet, &idle).is_ok());
+        assert_eq!(betting_table.player_count(), 2);
+
+        assert_eq!(betting_table.prune_empty_players(), 1);
+        assert_eq!(betting_table.player_count(), 1);
+        assert_eq!(betting_table.get_bets_for(&active), vec![bet]);
+    }
+    #[test]
+    fn pruning_forgets_everything_about_a_removed_player() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            placement_cooldown: Some(10),
+            ..Default::default()
+        });
+        let player = Player::create("Nuyts".into(), None);
+        let bet = Bet::FastestLap(Driver::LEC);
+        assert!(betting_table.place_at(bet.clone(), &player, 0).is_ok());
+        assert!(betting_table.set_banker(&player, &bet).is_ok());
+        betting_table.void_bets_matching(|_| true);
+        assert_eq!(betting_table.voided_bets_for(&player), vec![bet.clone()]);
+
+        assert_eq!(betting_table.prune_empty_players(), 1);
+        assert_eq!(betting_table.player_count(), 0);
+        assert!(betting_table.voided_bets_for(&player).is_empty());
+        assert!(betting_table.bankers.is_empty());
+        assert_eq!(betting_table.players.id_of(&player), None);
+        // the cooldown of the pruned player no longer applies
+        assert!(betting_table.place_at(bet, &player, 1).is_ok());
+    }
+    #[test]
+    fn bets_are_grouped_by_category() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        assert_eq!(
+            Bet::WillHaveSafetyCar(true).category(),
+            BetCategory::Novelty
+        );
+        assert_eq!(finish_position.category(), BetCategory::Race);
+
+        assert!(betting_table
+            .place(finish_position.clone(), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::PolePosition(Driver::LEC), &player)
+            .is_ok());
+
+        assert_eq!(
+            betting_table.bets_in_category(&player, BetCategory::Race),
+            vec![finish_position]
+        );
+        assert_eq!(
+            betting_table.bets_in_category(&player, BetCategory::Qualifying),
+            vec![Bet::PolePosition(Driver::LEC)]
+        );
+        assert!(betting_table
+            .bets_in_category(&player, BetCategory::Sprint)
+            .is_empty());
+    }
+    #[test]
+    fn cancelling_an_outcome_reverts_its_score() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &player)
+            .is_ok());
+
+        let reversed = Outcome {
+            outcome: Bet::DoesNotFinish(Driver::ALB),
+            reward: 400,
+            registered_at: None,
+        };
+        betting_table.register_outcome(reversed.clone());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+        assert_eq!(betting_table.results().get(&player).unwrap(), &500);
+
+        assert_eq!(betting_table.cancel_outcome(&reversed), Ok(()));
+        assert_eq!(betting_table.results().get(&player).unwrap(), &100);
+        assert_eq!(
+            betting_table.cancel_outcome(&reversed),
+            Err(OutcomeNotFound { outcome: reversed })
+        );
+    }
+    #[test]
+    fn disqualified_driver_settles_does_not_finish_bets() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALO), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &player)
+            .is_ok());
+
+        let results = [
+            DriverResult {
+                driver: Driver::VER,
+                status: DriverStatus::Finished,
+                position: Some(Position::new(1)),
+            },
+            DriverResult {
+                driver: Driver::ALO,
+                status: DriverStatus::DSQ,
+                position: None,
+            },
+            DriverResult {
+                driver: Driver::ALB,
+                status: DriverStatus::DNF,
+                position: None,
+            },
+        ];
+        let outcomes = Outcome::from_driver_results(&results, 100);
+        assert_eq!(outcomes.len(), 3);
+        for outcome in outcomes {
+            betting_table.register_outcome(outcome);
+        }
+
+        assert_eq!(betting_table.results().get(&player).unwrap(), &200);
+    }
+    #[test]
+    fn unbet_drivers_excludes_backed_drivers() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert_eq!(betting_table.unbet_drivers(&player).len(), 20);
+
+        let bet = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        assert!(betting_table.place(bet, &player).is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &player)
+            .is_ok());
+
+        let unbet_drivers = betting_table.unbet_drivers(&player);
+        assert_eq!(unbet_drivers.len(), 19);
+        assert!(!unbet_drivers.contains(&Driver::VER));
+        assert!(unbet_drivers.contains(&Driver::NOR));
+    }
+    #[test]
+    fn finish_positions_beyond_the_grid_are_rejected() {
+        let race = Race::new(Location::Monaco, 2022).with_grid_size(18);
+        let mut betting_table = BettingTable::for_race(race);
+        let player = Player::create("Nuyts".into(), None);
+
+        let out_of_grid = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(19),
+        };
+        assert_eq!(
+            betting_table.place(out_of_grid, &player),
+            Err(BetError::PositionOutOfGrid(Position::new(19)))
+        );
+
+        let last_place = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(18),
+        };
+        assert!(betting_table.place(last_place, &player).is_ok());
+    }
+    #[test]
+    fn only_the_first_retiree_wins_first_retirement() {
+        let mut betting_table = BettingTable::new();
+        let first = Player::create("first".into(), None);
+        let later = Player::create("later".into(), None);
+
+        assert!(betting_table
+            .place(Bet::FirstRetirement(Driver::MSC), &first)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FirstRetirement(Driver::ALB), &first)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::FirstRetirement(Driver::ALB), &later)
+            .is_ok());
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FirstRetirement(Driver::MSC),
+            reward: 750,
+            registered_at: None,
+        });
+        for driver in [Driver::MSC, Driver::ALB] {
+            betting_table.register_outcome(Outcome {
+                outcome: Bet::DoesNotFinish(driver),
+                reward: 100,
+                registered_at: None,
+            });
+        }
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&first).unwrap(), &750);
+        assert_eq!(scores.get(&later), None);
+    }
+    #[test]
+    fn tied_players_share_a_rank() {
+        let mut betting_table = BettingTable::new();
+        let players = [
+            Player::create("anna".into(), Some(Multiplier::X3)),
+            Player::create("bob".into(), Some(Multiplier::X2)),
+            Player::create("carl".into(), Some(Multiplier::X2)),
+            Player::create("dave".into(), None),
+        ];
+        for player in &players {
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::LEC), player)
+                .is_ok());
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let ranked = betting_table.ranked();
+        let ranks: Vec<usize> = ranked.iter().map(|ranked| ranked.rank).collect();
+        assert_eq!(ranks, vec![1, 2, 2, 4]);
+        assert_eq!(
+            ranked[2],
+            RankedPlayer {
+                rank: 2,
+                player: players[2].clone(),
+                score: 200,
+            }
+        );
+    }
+    #[test]
+    fn parlay_needs_two_compatible_legs() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+
+        assert!(betting_table
+            .place(Bet::Parlay(vec![winning_position.clone()]), &player)
+            .is_err());
+        assert!(betting_table
+            .place(
+                Bet::Parlay(vec![
+                    winning_position.clone(),
+                    Bet::FinishPosition {
+                        driver: Driver::HAM,
+                        position: Position::new(1),
+                    },
+                ]),
+                &player
+            )
+            .is_err());
+        let parlay = Bet::Parlay(vec![winning_position.clone(), Bet::FastestLap(Driver::LEC)]);
+        assert!(betting_table
+            .place(
+                Bet::Parlay(vec![parlay.clone(), Bet::DoesNotFinish(Driver::ALB)]),
+                &player
+            )
+            .is_err());
+
+        // The legs don't clash with standalone bets on the same outcome
+        assert!(betting_table.place(winning_position, &player).is_ok());
+        assert!(betting_table.place(parlay.clone(), &player).is_ok());
+        assert!(betting_table.place(parlay, &player).is_err());
+    }
+    #[test]
+    fn parlay_pays_only_when_every_leg_wins() {
+        let mut betting_table = BettingTable::new();
+        let winner = Player::create("winner".into(), None);
+        let loser = Player::create("loser".into(), None);
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+
+        assert!(betting_table
+            .place(
+                Bet::Parlay(vec![winning_position.clone(), Bet::FastestLap(Driver::LEC)]),
+                &winner
+            )
+            .is_ok());
+        assert!(betting_table
+            .place(
+                Bet::Parlay(vec![winning_position.clone(), Bet::FastestLap(Driver::HAM)]),
+                &loser
+            )
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: winning_position,
+            reward: 100,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 50,
+            registered_at: None,
+        });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&winner).unwrap(), &300);
+        assert_eq!(scores.get(&loser), None);
+    }
+    #[test]
+    fn projected_standings_fold_in_assumptions() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), Some(Multiplier::X2));
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+
+        assert!(betting_table
+            .place(winning_position.clone(), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::BothFinish(Driver::VER, Driver::HAM), &demi)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &demi)
+            .is_ok());
+        // Halfway through the race only the winner is known
+        betting_table.register_outcome(Outcome {
+            outcome: winning_position.clone(),
+            reward: 50,
+            registered_at: None,
+        });
+
+        let assume = HashMap::from([
+            (winning_position, false),
+            (Bet::WillHaveSafetyCar(true), true),
+            (Bet::BothFinish(Driver::VER, Driver::HAM), true),
+            (Bet::FastestLap(Driver::LEC), false),
+        ]);
+        let projected = betting_table.projected_standings(&assume);
+        // An assumption never takes away what is already won
+        assert_eq!(projected.get(&michiel).unwrap(), &150);
+        assert_eq!(projected.get(&demi).unwrap(), &400);
+        assert_eq!(betting_table.results().get(&demi), None);
+    }
+    #[test]
+    fn most_voted_driver_becomes_driver_of_the_day() {
+        let mut betting_table = BettingTable::new();
+        let votes = HashMap::from([(Driver::VER, 120), (Driver::ALB, 340), (Driver::HAM, 90)]);
+
+        assert_eq!(
+            betting_table.resolve_driver_of_the_day(votes, 100),
+            Some(Driver::ALB)
+        );
+        assert_eq!(
+            betting_table.outcomes,
+            vec![Outcome {
+                outcome: Bet::DriverOfTheDay(Driver::ALB),
+                reward: 100,
+                registered_at: None,
+            }]
+        );
+        assert_eq!(
+            betting_table.resolve_driver_of_the_day(HashMap::new(), 100),
+            None
+        );
+    }
+    #[test]
+    fn driver_of_the_day_tie_goes_to_the_best_finisher() {
+        let mut betting_table = BettingTable::new();
+        for outcome in Outcome::from_classification(&[Driver::VER, Driver::HAM, Driver::LEC], 10) {
+            betting_table.register_outcome(outcome);
+        }
+        let votes = HashMap::from([(Driver::LEC, 200), (Driver::HAM, 200), (Driver::ALB, 200)]);
+
+        assert_eq!(
+            betting_table.resolve_driver_of_the_day(votes, 100),
+            Some(Driver::HAM)
+        );
+    }
+    #[test]
+    fn placements_within_the_cooldown_are_rate_limited() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            placement_cooldown: Some(10),
+            ..Default::default()
+        });
+        let player = Player::create("Nuyts".into(), None);
+
+        assert!(betting_table
+            .place_at(Bet::FastestLap(Driver::LEC), &player, 100)
+            .is_ok());
+        assert_eq!(
+            betting_table.place_at(Bet::DoesNotFinish(Driver::ALB), &player, 105),
+            Err(BetError::RateLimited(110))
+        );
+        assert!(betting_table
+            .place_at(Bet::DoesNotFinish(Driver::ALB), &player, 110)
+            .is_ok());
+        assert_eq!(betting_table.get_bets_for(&player).len(), 2);
+    }
+    #[test]
+    fn placements_are_not_rate_limited_by_default() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+
+        assert!(betting_table
+            .place_at(Bet::FastestLap(Driver::LEC), &player, 100)
+            .is_ok());
+        assert!(betting_table
+            .place_at(Bet::DoesNotFinish(Driver::ALB), &player, 100)
+            .is_ok());
+    }
+    #[test]
+    fn exact_finish_slips_are_riskier_than_safety_car_slips() {
+        let mut betting_table = BettingTable::new();
+        let daring = Player::create("daring".into(), None);
+        let careful = Player::create("careful".into(), None);
+
+        for (driver, position) in [(Driver::VER, 1), (Driver::HAM, 2)] {
+            let bet = Bet::FinishPosition {
+                driver,
+                position: Position::new(position),
+            };
+            assert_eq!(bet.risk_tier(), RiskTier::High);
+            assert!(betting_table.place(bet, &daring).is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &careful)
+            .is_ok());
+
+        assert_eq!(betting_table.risk_score(&daring), 10);
+        assert_eq!(betting_table.risk_score(&careful), 1);
+        assert_eq!(
+            betting_table.risk_score(&Player::create("idle".into(), None)),
+            0
+        );
+    }
+    #[test]
+    fn track_record_bets_compare_the_fastest_lap_with_the_record() {
+        let score_with = |lap_time: f64| {
+            let mut betting_table = BettingTable::new();
+            let breaks = Player::create("breaks".into(), None);
+            let holds = Player::create("holds".into(), None);
+            assert!(betting_table
+                .place(Bet::BreaksTrackRecord(true), &breaks)
+                .is_ok());
+            assert!(betting_table
+                .place(Bet::BreaksTrackRecord(false), &holds)
+                .is_ok());
+            assert!(betting_table
+                .place(Bet::BreaksTrackRecord(true), &holds)
+                .is_err());
+
+            let outcome = Outcome::from_fastest_lap_time(Location::Monaco, lap_time, 100).unwrap();
+            betting_table.register_outcome(outcome);
+            let scores = betting_table.results();
+            (scores.get(&breaks).copied(), scores.get(&holds).copied())
+        };
+
+        // The Monaco record is a 1:12.909
+        assert_eq!(score_with(72.5), (Some(100), None));
+        assert_eq!(score_with(74.0), (None, Some(100)));
+    }
+    #[test]
+    fn dead_heat_pays_both_bettors_half() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        let loser = Player::create("loser".into(), None);
+        for (player, driver) in [
+            (&michiel, Driver::VER),
+            (&demi, Driver::HAM),
+            (&loser, Driver::LEC),
+        ] {
+            let bet = Bet::FinishPosition {
+                driver,
+                position: Position::new(3),
+            };
+            assert!(betting_table.place(bet, player).is_ok());
+        }
+
+        betting_table.register_dead_heat(&[Driver::VER, Driver::HAM], Position::new(3), 100);
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&michiel).unwrap(), &50);
+        assert_eq!(scores.get(&demi).unwrap(), &50);
+        assert_eq!(scores.get(&loser), None);
+    }
+    #[test]
+    fn winnings_are_capped_after_the_multiplier() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            max_winnings: Some(150),
+            ..Default::default()
+        });
+        let capped = Player::create("capped".into(), Some(Multiplier::X2));
+        let untouched = Player::create("untouched".into(), None);
+
+        for player in [&capped, &untouched] {
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::LEC), player)
+                .is_ok());
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&capped).unwrap(), &150);
+        assert_eq!(scores.get(&untouched).unwrap(), &100);
+    }
+    #[test]
+    fn placing_with_the_same_key_places_a_single_bet() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let bet = Bet::FastestLap(Driver::LEC);
+
+        let first = betting_table.place_idempotent(bet.clone(), &player, "request-1");
+        let retry = betting_table.place_idempotent(bet.clone(), &player, "request-1");
+        assert_eq!(first, Ok(bet.clone()));
+        assert_eq!(retry, Ok(bet.clone()));
+        assert_eq!(betting_table.get_bets_for(&player), vec![bet.clone()]);
+
+        assert!(betting_table
+            .place_idempotent(bet, &player, "request-2")
+            .is_err());
+    }
+    #[test]
+    fn keys_of_idempotent_placements_belong_to_a_single_player() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        let bet = Bet::FastestLap(Driver::LEC);
+
+        assert!(betting_table
+            .place_idempotent(bet.clone(), &michiel, "request-1")
+            .is_ok());
+        assert_eq!(
+            betting_table.place_idempotent(bet.clone(), &demi, "request-1"),
+            Ok(bet.clone())
+        );
+        assert_eq!(betting_table.get_bets_for(&demi), vec![bet.clone()]);
+
+        assert_eq!(
+            betting_table.place_idempotent(Bet::FastestLap(Driver::HAM), &michiel, "request-1"),
+            Err(BetError::KeyReused(bet))
+        );
+    }
+    #[test]
+    fn failed_idempotent_placements_can_be_retried() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let bet = Bet::FastestLap(Driver::LEC);
+        assert!(betting_table.place(bet.clone(), &player).is_ok());
+
+        assert!(betting_table
+            .place_idempotent(bet.clone(), &player, "request-1")
+            .is_err());
+        assert!(betting_table.cancel(&bet, &player).is_ok());
+        assert_eq!(
+            betting_table.place_idempotent(bet.clone(), &player, "request-1"),
+            Ok(bet)
+        );
+    }
+    #[test]
+    fn conditional_pays_only_when_the_condition_wins() {
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        let if_safety_car_then_ver_wins = Bet::Conditional {
+            if_bet: Box::new(Bet::WillHaveSafetyCar(true)),
+            then_bet: Box::new(winning_position.clone()),
+        };
+        let score_with = |safety_car: bool| {
+            let mut betting_table = BettingTable::new();
+            let player = Player::create("Nuyts".into(), None);
+            assert!(betting_table
+                .place(if_safety_car_then_ver_wins.clone(), &player)
+                .is_ok());
+            for outcome in [Bet::WillHaveSafetyCar(safety_car), winning_position.clone()] {
+                betting_table.register_outcome(Outcome {
+                    outcome,
+                    reward: 100,
+                    registered_at: None,
+                });
+            }
+            betting_table.results().get(&player).copied()
+        };
+
+        assert_eq!(score_with(true), Some(100));
+        assert_eq!(score_with(false), None);
+    }
+    #[test]
+    fn conditional_rejects_contradicting_sides() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let conditional = |if_bet: Bet, then_bet: Bet| Bet::Conditional {
+            if_bet: Box::new(if_bet),
+            then_bet: Box::new(then_bet),
+        };
+
+        let contradiction =
+            conditional(Bet::WillHaveSafetyCar(true), Bet::WillHaveSafetyCar(false));
+        let self_referential =
+            conditional(Bet::FastestLap(Driver::LEC), Bet::FastestLap(Driver::LEC));
+        let nested = conditional(
+            Bet::WillHaveSafetyCar(true),
+            conditional(
+                Bet::FastestLap(Driver::LEC),
+                Bet::DoesNotFinish(Driver::ALB),
+            ),
+        );
+        for bet in [contradiction, self_referential, nested] {
+            assert!(betting_table.place(bet, &player).is_err());
+        }
+    }
+    #[test]
+    fn current_leaders_include_every_tied_player() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        let fastest_lap = Bet::FastestLap(Driver::LEC);
+        assert_eq!(betting_table.current_leaders(), vec![]);
+
+        assert!(betting_table.place(fastest_lap.clone(), &michiel).is_ok());
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &michiel)
+            .is_ok());
+        assert!(betting_table.place(fastest_lap.clone(), &demi).is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: fastest_lap,
+            reward: 100,
+            registered_at: None,
+        });
+        assert_eq!(
+            betting_table.current_leaders(),
+            vec![demi.clone(), michiel.clone()]
+        );
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::DoesNotFinish(Driver::ALB),
+            reward: 100,
+            registered_at: None,
+        });
+        assert_eq!(betting_table.current_leaders(), vec![michiel]);
+    }
+    #[test]
+    fn hedging_places_both_answers_when_allowed() {
+        let place_both_answers = |allow_hedging: bool| {
+            let mut betting_table = BettingTable::with_config(PoolConfig {
+                allow_hedging,
+                ..Default::default()
+            });
+            let player = Player::create("Nuyts".into(), None);
+            assert!(betting_table
+                .place(Bet::WillHaveSafetyCar(true), &player)
+                .is_ok());
+            assert!(betting_table
+                .place(Bet::WillHaveSafetyCar(true), &player)
+                .is_err());
+            betting_table
+                .place(Bet::WillHaveSafetyCar(false), &player)
+                .is_ok()
+        };
+
+        assert!(place_both_answers(true));
+        assert!(!place_both_answers(false));
+    }
+    #[test]
+    fn best_per_category_only_counts_the_best_bet_of_a_category() {
+        let score_with = |scoring: ScoringMode| {
+            let mut betting_table = BettingTable::with_config(PoolConfig {
+                scoring,
+                ..Default::default()
+            });
+            let player = Player::create("Nuyts".into(), None);
+            let winning_position = Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(1),
+            };
+            for bet in [
+                winning_position.clone(),
+                Bet::FastestLap(Driver::LEC),
+                Bet::WillHaveSafetyCar(true),
+            ] {
+                assert!(betting_table.place(bet, &player).is_ok());
+            }
+            for (outcome, reward) in [
+                (winning_position, 100),
+                (Bet::FastestLap(Driver::LEC), 40),
+                (Bet::WillHaveSafetyCar(true), 10),
+            ] {
+                betting_table.register_outcome(Outcome {
+                    outcome,
+                    reward,
+                    registered_at: None,
+                });
+            }
+            *betting_table.results().get(&player).unwrap()
+        };
+
+        assert_eq!(score_with(ScoringMode::Sum), 150);
+        assert_eq!(score_with(ScoringMode::BestPerCategory), 110);
+    }
+    #[test]
+    fn unbet_positions_exclude_the_claimed_ones() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        for (driver, position) in [(Driver::VER, 1), (Driver::HAM, 5)] {
+            let bet = Bet::FinishPosition {
+                driver,
+                position: Position::new(position),
+            };
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+
+        let unbet = betting_table.unbet_positions(&player);
+        assert_eq!(unbet.len(), 18);
+        assert!(!unbet.contains(&Position::new(1)));
+        assert!(!unbet.contains(&Position::new(5)));
+        assert_eq!(unbet.first(), Some(&Position::new(2)));
+
+        let mut betting_table =
+            BettingTable::for_race(Race::new(Location::Monaco, 2022).with_grid_size(6));
+        let bet = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        assert!(betting_table.place(bet, &player).is_ok());
+        assert_eq!(
+            betting_table.unbet_positions(&player),
+            (2..=6).map(Position::new).collect::<Vec<Position>>()
+        );
+    }
+    #[test]
+    fn outcomes_with_a_different_reward_share_their_subject() {
+        let provisional = Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 50,
+            registered_at: None,
+        };
+        let final_outcome = Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        };
+        let other = Outcome {
+            outcome: Bet::FastestLap(Driver::HAM),
+            reward: 50,
+            registered_at: None,
+        };
+
+        assert!(provisional.same_subject(&final_outcome));
+        assert_ne!(provisional, final_outcome);
+        assert!(!provisional.same_subject(&other));
+    }
+    #[test]
+    fn best_rookie_only_accepts_rookies() {
+        let mut betting_table = BettingTable::for_race(Race::new(Location::Monaco, 2022));
+        let player = Player::create("Nuyts".into(), None);
+
+        assert_eq!(
+            betting_table.place(Bet::BestRookie(Driver::HAM), &player),
+            Err(BetError::NotARookie(Driver::HAM))
+        );
+        assert!(betting_table
+            .place(Bet::BestRookie(Driver::ZHO), &player)
+            .is_ok());
+    }
+    #[test]
+    fn best_rookie_needs_a_race_to_know_the_rookies() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+
+        assert_eq!(
+            betting_table.place(Bet::BestRookie(Driver::ZHO), &player),
+            Err(BetError::SeasonUnknown)
+        );
+    }
+    #[test]
+    fn best_rookie_pays_the_highest_finishing_rookie() {
+        let mut betting_table = BettingTable::for_race(Race::new(Location::Monaco, 2021));
+        let winner = Player::create("winner".into(), None);
+        let loser = Player::create("loser".into(), None);
+        assert!(betting_table
+            .place(Bet::BestRookie(Driver::TSU), &winner)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::BestRookie(Driver::MSC), &loser)
+            .is_ok());
+
+        for outcome in
+            Outcome::from_classification(&[Driver::VER, Driver::HAM, Driver::TSU, Driver::MSC], 10)
+        {
+            betting_table.register_outcome(outcome);
+        }
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&winner).unwrap(), &250);
+        assert_eq!(scores.get(&loser), None);
+    }
+    #[test]
+    fn constructor_one_two_needs_both_drivers_on_top() {
+        let score_with = |classification: &[Driver]| {
+            let mut betting_table = BettingTable::new();
+            let player = Player::create("Nuyts".into(), None);
+            assert!(betting_table
+                .place(Bet::ConstructorOneTwo(Constructor::Ferrari), &player)
+                .is_ok());
+            assert!(betting_table
+                .place(Bet::ConstructorOneTwo(Constructor::RedBull), &player)
+                .is_err());
+            for outcome in Outcome::from_classification(classification, 10) {
+                betting_table.register_outcome(outcome);
+            }
+            betting_table.results().get(&player).copied()
+        };
+
+        assert_eq!(
+            score_with(&[Driver::SAI, Driver::LEC, Driver::VER]),
+            Some(1500)
+        );
+        assert_eq!(score_with(&[Driver::LEC, Driver::VER, Driver::SAI]), None);
+    }
+    #[test]
+    fn first_team_to_score_is_the_team_of_the_best_placed_points_finisher() {
+        let mut betting_table = BettingTable::new();
+        let midfield = Player::create("midfield".into(), None);
+        let favorite = Player::create("favorite".into(), None);
+        assert!(betting_table
+            .place(Bet::FirstTeamToScore(Constructor::Haas), &midfield)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FirstTeamToScore(Constructor::RedBull), &midfield)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::FirstTeamToScore(Constructor::RedBull), &favorite)
+            .is_ok());
+
+        // a finish outside of the points never scores
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(11),
+            },
+            reward: 10,
+            registered_at: None,
+        });
+        assert_eq!(betting_table.results().get(&favorite), None);
+
+        for outcome in Outcome::from_classification(&[Driver::MAG, Driver::HAM, Driver::PER], 10) {
+            betting_table.register_outcome(outcome);
+        }
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&midfield).unwrap(), &150);
+        assert_eq!(scores.get(&favorite), None);
+    }
+    #[test]
+    fn copying_a_slip_skips_the_bets_that_clash() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        for bet in [
+            Bet::FastestLap(Driver::LEC),
+            Bet::WillHaveSafetyCar(true),
+            Bet::DoesNotFinish(Driver::ALB),
+        ] {
+            assert!(betting_table.place(bet, &michiel).is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::VER), &demi)
+            .is_ok());
+
+        assert_eq!(betting_table.copy_slip(&michiel, &demi), Ok(2));
+        assert_eq!(
+            betting_table.get_bets_for(&demi),
+            vec![
+                Bet::FastestLap(Driver::VER),
+                Bet::WillHaveSafetyCar(true),
+                Bet::DoesNotFinish(Driver::ALB),
+            ]
+        );
+        assert_eq!(betting_table.get_bets_for(&michiel).len(), 3);
+    }
+    #[test]
+    fn a_failing_copy_leaves_the_slip_as_is() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        let out_of_grid = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(19),
+        };
+        for bet in [Bet::FastestLap(Driver::LEC), out_of_grid] {
+            assert!(betting_table.place(bet, &michiel).is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &demi)
+            .is_ok());
+        betting_table.race = Some(Race::new(Location::Monaco, 2022).with_grid_size(18));
+
+        assert_eq!(
+            betting_table.copy_slip(&michiel, &demi),
+            Err(BetError::PositionOutOfGrid(Position::new(19)))
+        );
+        assert_eq!(
+            betting_table.get_bets_for(&demi),
+            vec![Bet::WillHaveSafetyCar(true)]
+        );
+    }
+    #[test]
+    fn resolving_no_safety_car_pays_the_players_who_bet_against_it() {
+        let mut betting_table = BettingTable::new();
+        let against = Player::create("against".into(), None);
+        let for_it = Player::create("for it".into(), None);
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(false), &against)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &for_it)
+            .is_ok());
+
+        betting_table.resolve_safety_car(true, 100);
+        betting_table.resolve_safety_car(false, 500);
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&against).unwrap(), &500);
+        assert_eq!(scores.get(&for_it), None);
+        assert_eq!(betting_table.outcomes.len(), 1);
+    }
+    #[test]
+    fn used_up_categories_are_no_longer_available() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert_eq!(
+            betting_table.available_bet_kinds(&player),
+            vec![
+                BetCategory::Race,
+                BetCategory::Qualifying,
+                BetCategory::Novelty
+            ]
+        );
+
+        for bet in [
+            Bet::DriverOfTheDay(Driver::HAM),
+            Bet::WillHaveSafetyCar(true),
+            Bet::BreaksTrackRecord(false),
+            Bet::NumberOfPenalties(2),
+            Bet::RaceIsWet(false),
+            Bet::FirstSafetyCarLap(10),
+            Bet::OvertakeCount(30),
+        ]
+        .into_iter()
+        .chain(Driver::all().map(Bet::AnyDriverPenalized))
+        {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+        assert_eq!(
+            betting_table.available_bet_kinds(&player),
+            vec![BetCategory::Race, BetCategory::Qualifying]
+        );
+    }
+    #[test]
+    fn start_position_bets_are_settled_by_the_grid() {
+        let mut betting_table = BettingTable::for_race(Race::new(Location::Monaco, 2022));
+        let player = Player::create("Nuyts".into(), None);
+        let start_position = Bet::StartPosition {
+            driver: Driver::LEC,
+            position: Position::new(1),
+        };
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::LEC,
+            position: Position::new(1),
+        };
+
+        assert!(betting_table.place(start_position, &player).is_ok());
+        assert!(betting_table.place(finish_position, &player).is_ok());
+        assert!(betting_table
+            .place(
+                Bet::StartPosition {
+                    driver: Driver::VER,
+                    position: Position::new(1),
+                },
+                &player
+            )
+            .is_err());
+        assert_eq!(betting_table.results().get(&player), None);
+
+        betting_table
+            .race_mut()
+            .unwrap()
+            .set_grid(vec![Driver::LEC, Driver::SAI, Driver::PER]);
+        assert_eq!(betting_table.results().get(&player).unwrap(), &100);
+    }
+    #[test]
+    fn negation_pays_once_the_inner_bet_lost() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let ver_wins = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        let ver_does_not_win = Bet::Not(Box::new(ver_wins.clone()));
+
+        assert!(betting_table.place(ver_does_not_win, &player).is_ok());
+        assert!(betting_table.place(ver_wins, &player).is_err());
+        assert!(betting_table
+            .place(
+                Bet::Not(Box::new(Bet::BothFinish(Driver::VER, Driver::HAM))),
+                &player
+            )
+            .is_err());
+        // Nothing decided the finish of VER yet
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::LEC,
+                position: Position::new(1),
+            },
+            reward: 100,
+            registered_at: None,
+        });
+        assert_eq!(betting_table.results().get(&player), None);
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(2),
+            },
+            reward: 100,
+            registered_at: None,
+        });
+        assert_eq!(betting_table.results().get(&player).unwrap(), &50);
+    }
+    #[test]
+    fn every_category_closes_at_its_own_deadline() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            deadlines: HashMap::from([(BetCategory::Qualifying, 100), (BetCategory::Race, 200)]),
+            ..Default::default()
+        });
+        let player = Player::create("Nuyts".into(), None);
+
+        assert!(betting_table
+            .place_at(Bet::PolePosition(Driver::LEC), &player, 99)
+            .is_ok());
+        assert_eq!(
+            betting_table.place_at(
+                Bet::StartPosition {
+                    driver: Driver::VER,
+                    position: Position::new(2),
+                },
+                &player,
+                150
+            ),
+            Err(BetError::BettingClosed(BetCategory::Qualifying))
+        );
+        assert!(betting_table
+            .place_at(Bet::FastestLap(Driver::LEC), &player, 150)
+            .is_ok());
+        assert!(betting_table
+            .place_at(Bet::DriverOfTheDay(Driver::HAM), &player, 1000)
+            .is_ok());
+    }
+    #[test]
+    fn redistributing_forfeited_winnings_keeps_the_total_pot() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            max_winnings: Some(150),
+            ..Default::default()
+        });
+        let capped = Player::create("capped".into(), Some(Multiplier::X3));
+        let small = Player::create("small".into(), None);
+        let smaller = Player::create("smaller".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &capped)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &small)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &smaller)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::DoesNotFinish(Driver::ALB),
+            reward: 50,
+            registered_at: None,
+        });
+
+        // 300 + 100 + 50 before the cap, 150 of it is forfeited. Small would get 100 of it,
+        // but only has room for 50, so the other 50 goes to smaller as well
+        let scores = betting_table.redistribute_forfeited();
+        assert_eq!(scores.values().sum::<u64>(), 450);
+        assert_eq!(scores.get(&capped).unwrap(), &150);
+        assert_eq!(scores.get(&small).unwrap(), &150);
+        assert_eq!(scores.get(&smaller).unwrap(), &150);
+    }
+    #[test]
+    fn players_with_the_same_name_keep_separate_slips() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let doubled = Player::create("michiel".into(), Some(Multiplier::X2));
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &doubled)
+            .is_ok());
+        assert!(betting_table
+            .cancel(&Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &michiel)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        assert_eq!(betting_table.player_count(), 2);
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&michiel), None);
+        assert_eq!(scores.get(&doubled).unwrap(), &200);
+    }
+    #[test]
+    fn scoring_at_an_earlier_moment_leaves_out_later_outcomes() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &player)
+            .is_ok());
+        betting_table.register_outcome_at(
+            Outcome {
+                outcome: Bet::WillHaveSafetyCar(true),
+                reward: 100,
+                registered_at: None,
+            },
+            60,
+        );
+        betting_table.register_outcome_at(
+            Outcome {
+                outcome: Bet::FastestLap(Driver::LEC),
+                reward: 50,
+                registered_at: None,
+            },
+            120,
+        );
+
+        assert_eq!(betting_table.outcomes[1].registered_at(), Some(120));
+        assert_eq!(betting_table.score_at(&player, 30), 0);
+        assert_eq!(betting_table.score_at(&player, 60), 100);
+        assert_eq!(betting_table.score_at(&player, 119), 100);
+        assert_eq!(betting_table.score_at(&player, 120), 150);
+        assert_eq!(betting_table.results().get(&player).unwrap(), &150);
+
+        assert!(betting_table
+            .place(Bet::PolePosition(Driver::LEC), &player)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::PolePosition(Driver::LEC),
+            reward: 10,
+            registered_at: None,
+        });
+        assert_eq!(betting_table.score_at(&player, 120), 150);
+        assert_eq!(betting_table.results().get(&player).unwrap(), &160);
+    }
+    #[test]
+    fn penalty_bets_settle_against_the_penalties_of_the_race() {
+        let mut betting_table = BettingTable::new();
+        let exact = Player::create("exact".into(), None);
+        let wrong = Player::create("wrong".into(), None);
+        assert!(betting_table
+            .place(Bet::NumberOfPenalties(3), &exact)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::NumberOfPenalties(2), &exact)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::AnyDriverPenalized(Driver::MAG), &exact)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::AnyDriverPenalized(Driver::OCO), &exact)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::NumberOfPenalties(2), &wrong)
+            .is_ok());
+
+        for outcome in Outcome::from_penalties(&[Driver::MAG, Driver::ALO, Driver::MAG], 40) {
+            betting_table.register_outcome(outcome);
+        }
 
-        let result = betting_table.place(Bet::FastestLap(Driver::LEC), &demi);
-        assert!(result.is_ok());
-        let result = betting_table.place(Bet::FastestLap(Driver::HAM), &demi);
-        assert!(result.is_err());
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&exact).unwrap(), &80);
+        assert_eq!(scores.get(&wrong), None);
+    }
+    #[test]
+    fn penalty_counts_beyond_a_u8_saturate() {
+        let outcomes = Outcome::from_penalties(&[Driver::MAG; 300], 40);
 
-        let result = betting_table.place(Bet::DriverOfTheDay(Driver::LEC), &demi);
-        assert!(result.is_ok());
-        let result = betting_table.place(Bet::DriverOfTheDay(Driver::HAM), &demi);
-        assert!(result.is_err());
+        assert_eq!(outcomes[0].outcome, Bet::NumberOfPenalties(u8::MAX));
+        assert_eq!(outcomes[1].outcome, Bet::AnyDriverPenalized(Driver::MAG));
+    }
+    #[test]
+    fn overtaking_needs_one_point_more_than_the_gap() {
+        let mut betting_table = BettingTable::new();
+        let leader = Player::create("leader".into(), None);
+        let chaser = Player::create("chaser".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &leader)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &chaser)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::WillHaveSafetyCar(true),
+            reward: 40,
+            registered_at: None,
+        });
+
+        assert_eq!(betting_table.bets_to_overtake(&chaser, &leader), Some(61));
+        assert_eq!(betting_table.bets_to_overtake(&leader, &chaser), None);
+        assert_eq!(betting_table.bets_to_overtake(&leader, &leader), Some(1));
+    }
+    #[cfg(feature = "log")]
+    #[test]
+    fn placing_a_bet_logs_a_single_record() {
+        use log::kv::{Key, Value, VisitSource};
+        use std::sync::Mutex;
+
+        struct Fields(Vec<(String, String)>);
+        impl<'kvs> VisitSource<'kvs> for Fields {
+            fn visit_pair(
+                &mut self,
+                key: Key<'kvs>,
+                value: Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+        struct CapturingLogger(Mutex<Vec<Vec<(String, String)>>>);
+        impl log::Log for CapturingLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                let mut fields = Fields(Vec::new());
+                record.key_values().visit(&mut fields).unwrap();
+                self.0.lock().unwrap().push(fields.0);
+            }
+            fn flush(&self) {}
+        }
+        static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Info);
+
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("logged".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &player)
+            .is_ok());
+
+        // other tests may log in parallel, only keep the records of this player
+        let records: Vec<Vec<(String, String)>> = LOGGER
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fields| fields.contains(&("player".into(), "logged".into())))
+            .cloned()
+            .collect();
+        assert_eq!(
+            records,
+            vec![vec![
+                ("player".into(), "logged".into()),
+                ("bet".into(), "FastestLap(LEC)".into()),
+                ("result".into(), "Ok(())".into()),
+            ]]
+        );
+    }
+    #[test]
+    fn scoring_points_needs_a_top_ten_finish() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let finish_position = Bet::FinishPosition {
+            driver: Driver::NOR,
+            position: Position::new(8),
+        };
+        for bet in [
+            Bet::ScoresPoints(Driver::NOR),
+            finish_position,
+            Bet::ScoresPoints(Driver::ALB),
+        ] {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::ScoresPoints(Driver::NOR), &player)
+            .is_err());
+
+        for (driver, position) in [(Driver::NOR, 8), (Driver::ALB, 12)] {
+            betting_table.register_outcome(Outcome {
+                outcome: Bet::FinishPosition {
+                    driver,
+                    position: Position::new(position),
+                },
+                reward: 100,
+                registered_at: None,
+            });
+        }
+
+        // P8 pays the exact finish and the points finish, P12 pays nothing
+        assert_eq!(betting_table.results().get(&player).unwrap(), &150);
+    }
+    #[test]
+    fn nearest_rivals_are_closest_in_score_either_way() {
+        let mut betting_table = BettingTable::new();
+        let rewards = [("me", 100), ("far", 400), ("behind", 70), ("ahead", 140)];
+        for (index, (name, reward)) in rewards.into_iter().enumerate() {
+            let driver = Driver::all()[index];
+            assert!(betting_table
+                .place(
+                    Bet::DriverOfTheDay(driver),
+                    &Player::create(name.into(), None)
+                )
+                .is_ok());
+            betting_table.register_outcome(Outcome {
+                outcome: Bet::DriverOfTheDay(driver),
+                reward,
+                registered_at: None,
+            });
+        }
+
+        assert_eq!(
+            betting_table.nearest_rivals(&Player::create("me".into(), None), 2),
+            vec![
+                (Player::create("behind".into(), None), -30),
+                (Player::create("ahead".into(), None), 40),
+            ]
+        );
+    }
+    #[test]
+    fn calling_a_wet_race_pays_a_boosted_reward() {
+        let score_with = |wet: bool| {
+            let mut betting_table = BettingTable::new();
+            let player = Player::create("Nuyts".into(), None);
+            assert!(betting_table.place(Bet::RaceIsWet(wet), &player).is_ok());
+            assert!(betting_table.place(Bet::RaceIsWet(!wet), &player).is_err());
+            betting_table.register_outcome(Outcome {
+                outcome: Bet::RaceIsWet(wet),
+                reward: 100,
+                registered_at: None,
+            });
+            betting_table.results().get(&player).copied()
+        };
+
+        assert_eq!(score_with(true), Some(300));
+        assert_eq!(score_with(false), Some(100));
+    }
+    #[test]
+    fn scores_beyond_a_u64_are_an_error() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        for bet in [Bet::FastestLap(Driver::LEC), Bet::PolePosition(Driver::LEC)] {
+            assert!(betting_table.place(bet.clone(), &player).is_ok());
+            betting_table.register_outcome(Outcome {
+                outcome: bet,
+                reward: u64::MAX - 1,
+                registered_at: None,
+            });
+        }
+
+        assert_eq!(
+            betting_table.try_results(),
+            Err(ScoringOverflow { player }.into())
+        );
+    }
+    #[test]
+    fn multiplied_rewards_beyond_a_u64_are_an_error() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), Some(Multiplier::X3));
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &player)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: u64::MAX / 2,
+            registered_at: None,
+        });
 
+        assert_eq!(
+            betting_table.try_leaderboard(),
+            Err(ScoringOverflow { player }.into())
+        );
+    }
+    #[test]
+    fn team_totals_beyond_a_u64_are_an_error() {
+        let mut betting_table = BettingTable::new();
+        for name in ["michiel", "demi"] {
+            let player = Player::create(name.into(), None).with_favorite_team(Constructor::Ferrari);
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::LEC), &player)
+                .is_ok());
+        }
         betting_table.register_outcome(Outcome {
-            outcome: Bet::FinishPosition {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: u64::MAX / 2 + 1,
+            registered_at: None,
+        });
+
+        assert_eq!(
+            betting_table.try_team_standings(),
+            Err(ScoringError::TeamOverflow(Constructor::Ferrari))
+        );
+    }
+    #[test]
+    fn rival_scores_beyond_an_i64_are_an_error() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::PolePosition(Driver::LEC), &demi)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::PolePosition(Driver::LEC),
+            reward: u64::MAX,
+            registered_at: None,
+        });
+
+        assert_eq!(
+            betting_table.try_nearest_rivals(&michiel, 1),
+            Err(ScoringOverflow { player: demi }.into())
+        );
+    }
+    #[test]
+    fn passing_a_maxed_out_target_is_an_error() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::PolePosition(Driver::LEC), &demi)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::PolePosition(Driver::LEC),
+            reward: u64::MAX,
+            registered_at: None,
+        });
+
+        assert_eq!(
+            betting_table.try_bets_to_overtake(&michiel, &demi),
+            Err(ScoringOverflow { player: michiel }.into())
+        );
+    }
+    #[test]
+    fn suggestions_for_a_driver_leave_out_what_is_taken() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let finish_position = |driver, position| Bet::FinishPosition {
+            driver,
+            position: Position::new(position),
+        };
+        assert!(betting_table
+            .place(finish_position(Driver::VER, 1), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(finish_position(Driver::HAM, 2), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &player)
+            .is_ok());
+
+        let suggestions = betting_table.suggestions_for_driver(&player, Driver::PER);
+        assert!(!suggestions.contains(&finish_position(Driver::PER, 1)));
+        assert!(!suggestions.contains(&finish_position(Driver::PER, 2)));
+        assert!(suggestions.contains(&finish_position(Driver::PER, 3)));
+        assert!(!suggestions.contains(&Bet::FastestLap(Driver::PER)));
+        assert!(suggestions.contains(&Bet::DoesNotFinish(Driver::PER)));
+        assert!(suggestions
+            .iter()
+            .all(|suggestion| suggestion.drivers().contains(Driver::PER)));
+
+        // a driver with a finish bet can't get another finish position
+        let suggestions = betting_table.suggestions_for_driver(&player, Driver::VER);
+        assert!(!suggestions
+            .iter()
+            .any(|suggestion| matches!(suggestion, Bet::FinishPosition { .. })));
+    }
+    #[test]
+    fn an_outcome_settles_the_bets_it_wins() {
+        let mut betting_table = BettingTable::new();
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        let anna = Player::create("anna".into(), None);
+        let bob = Player::create("bob".into(), None);
+        let carl = Player::create("carl".into(), None);
+        for player in [&bob, &anna] {
+            assert!(betting_table
+                .place(winning_position.clone(), player)
+                .is_ok());
+        }
+        assert!(betting_table
+            .place(
+                Bet::FinishPosition {
+                    driver: Driver::VER,
+                    position: Position::new(2),
+                },
+                &carl
+            )
+            .is_ok());
+        let outcome = Outcome {
+            outcome: winning_position.clone(),
+            reward: 100,
+            registered_at: None,
+        };
+        betting_table.register_outcome(outcome.clone());
+
+        assert_eq!(
+            betting_table.settled_by(&outcome),
+            vec![(anna, winning_position.clone()), (bob, winning_position)]
+        );
+    }
+    #[test]
+    fn safety_car_lap_bets_win_within_the_tolerance() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            safety_car_lap_tolerance: 2,
+            ..Default::default()
+        });
+        let exact = Player::create("exact".into(), None);
+        let near = Player::create("near".into(), None);
+        let far = Player::create("far".into(), None);
+        for (player, lap) in [(&exact, 14), (&near, 16), (&far, 17)] {
+            assert!(betting_table
+                .place(Bet::FirstSafetyCarLap(lap), player)
+                .is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::FirstSafetyCarLap(20), &exact)
+            .is_err());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &exact)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FirstSafetyCarLap(14),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&exact).unwrap(), &100);
+        assert_eq!(scores.get(&near).unwrap(), &100);
+        assert_eq!(scores.get(&far), None);
+    }
+    #[test]
+    fn reward_table_shows_the_best_paying_bet_per_category() {
+        let betting_table = BettingTable::with_config(PoolConfig {
+            constructor_one_two_reward: 2000,
+            start_position_reward: 400,
+            projected_reward: 120,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            betting_table.reward_table(),
+            vec![
+                (BetCategory::Race, 2000),
+                (BetCategory::Qualifying, 400),
+                (BetCategory::Novelty, 360),
+            ]
+        );
+    }
+    #[test]
+    fn running_positions_only_count_for_projections() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table
+            .place(
+                Bet::FinishPosition {
+                    driver: Driver::VER,
+                    position: Position::new(1),
+                },
+                &player
+            )
+            .is_ok());
+
+        betting_table.update_running_position(Driver::VER, Position::new(1));
+        assert_eq!(
+            betting_table
+                .projected_standings(&HashMap::new())
+                .get(&player),
+            Some(&100)
+        );
+        assert_eq!(betting_table.results().get(&player), None);
+
+        betting_table.update_running_position(Driver::VER, Position::new(2));
+        assert_eq!(
+            betting_table
+                .projected_standings(&HashMap::new())
+                .get(&player),
+            None
+        );
+    }
+    #[test]
+    fn overtake_count_bets_win_exactly_or_within_the_tolerance() {
+        let outcome = Outcome {
+            outcome: Bet::OvertakeCount(42),
+            reward: 100,
+            registered_at: None,
+        };
+        let exact = Player::create("exact".into(), None);
+        let near = Player::create("near".into(), None);
+
+        let mut strict = BettingTable::new();
+        let mut tolerant = BettingTable::with_config(PoolConfig {
+            overtake_count_tolerance: 5,
+            ..Default::default()
+        });
+        for betting_table in [&mut strict, &mut tolerant] {
+            assert!(betting_table.place(Bet::OvertakeCount(42), &exact).is_ok());
+            assert!(betting_table.place(Bet::OvertakeCount(38), &near).is_ok());
+            assert!(betting_table.place(Bet::OvertakeCount(50), &near).is_err());
+            betting_table.register_outcome(outcome.clone());
+        }
+
+        assert_eq!(strict.results().get(&exact), Some(&100));
+        assert_eq!(strict.results().get(&near), None);
+        assert_eq!(tolerant.results().get(&exact), Some(&100));
+        assert_eq!(tolerant.results().get(&near), Some(&100));
+    }
+    #[test]
+    fn disqualification_moves_everyone_behind_up() {
+        let mut betting_table = BettingTable::new();
+        let winner = Player::create("winner".into(), None);
+        let runner_up = Player::create("runner up".into(), None);
+        assert!(betting_table
+            .place(
+                Bet::FinishPosition {
+                    driver: Driver::VER,
+                    position: Position::new(1),
+                },
+                &winner
+            )
+            .is_ok());
+        assert!(betting_table
+            .place(
+                Bet::FinishPosition {
+                    driver: Driver::LEC,
+                    position: Position::new(1),
+                },
+                &runner_up
+            )
+            .is_ok());
+        for (driver, position) in [(Driver::VER, 1), (Driver::LEC, 2), (Driver::HAM, 3)] {
+            betting_table.register_outcome(Outcome {
+                outcome: Bet::FinishPosition {
+                    driver,
+                    position: Position::new(position),
+                },
+                reward: 100,
+                registered_at: None,
+            });
+        }
+        assert_eq!(betting_table.results().get(&winner), Some(&100));
+
+        betting_table.apply_disqualification(Driver::VER);
+
+        assert_eq!(
+            betting_table.finish_position_of(Driver::LEC),
+            Some(Position::new(1))
+        );
+        assert_eq!(
+            betting_table.finish_position_of(Driver::HAM),
+            Some(Position::new(2))
+        );
+        assert_eq!(betting_table.finish_position_of(Driver::VER), None);
+        assert!(betting_table.retired_drivers().contains(Driver::VER));
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&winner), None);
+        assert_eq!(scores.get(&runner_up), Some(&100));
+    }
+    #[test]
+    fn disqualification_takes_a_car_off_the_finishers_count() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        assert!(betting_table.place(Bet::FinishersCount(1), &player).is_ok());
+        for outcome in [
+            Bet::FinishPosition {
                 driver: Driver::VER,
                 position: Position::new(1),
             },
-            reward: 1000,
+            Bet::FinishPosition {
+                driver: Driver::LEC,
+                position: Position::new(2),
+            },
+            Bet::FinishersCount(2),
+        ] {
+            betting_table.register_outcome(Outcome {
+                outcome,
+                reward: 100,
+                registered_at: None,
+            });
+        }
+        assert_eq!(betting_table.results().get(&player), None);
+
+        betting_table.apply_disqualification(Driver::LEC);
+
+        assert_eq!(betting_table.results().get(&player), Some(&100));
+    }
+    #[test]
+    fn stakes_are_paid_from_the_bankroll() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            bankroll: 100,
+            stake_per_bet: 10,
+            ..Default::default()
+        });
+        let player = Player::create("Nuyts".into(), None);
+        assert_eq!(betting_table.remaining_bankroll(&player), 100);
+
+        assert!(betting_table
+            .place_with_stake(Bet::FastestLap(Driver::VER), &player, 60)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::WillHaveSafetyCar(true), &player)
+            .is_ok());
+        assert_eq!(betting_table.remaining_bankroll(&player), 30);
+
+        assert_eq!(
+            betting_table.place_with_stake(Bet::DoesNotFinish(Driver::ALB), &player, 40),
+            Err(BetError::InsufficientBankroll(30))
+        );
+        assert_eq!(betting_table.get_bets_for(&player).len(), 2);
+
+        assert!(betting_table
+            .cancel(&Bet::FastestLap(Driver::VER), &player)
+            .is_ok());
+        assert_eq!(betting_table.remaining_bankroll(&player), 90);
+    }
+    #[test]
+    fn voided_bets_neither_win_nor_lose() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        };
+        for bet in [winning_position.clone(), Bet::WillHaveSafetyCar(true)] {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+        for outcome in [winning_position.clone(), Bet::WillHaveSafetyCar(true)] {
+            betting_table.register_outcome(Outcome {
+                outcome,
+                reward: 100,
+                registered_at: None,
+            });
+        }
+        assert_eq!(betting_table.results().get(&player), Some(&200));
+
+        betting_table.void_bets_matching(|bet| matches!(bet, Bet::FinishPosition { .. }));
+
+        assert_eq!(betting_table.results().get(&player), Some(&100));
+        assert_eq!(
+            betting_table.get_bets_for(&player),
+            vec![Bet::WillHaveSafetyCar(true)]
+        );
+        assert_eq!(
+            betting_table.voided_bets_for(&player),
+            vec![winning_position]
+        );
+    }
+    #[test]
+    fn pit_stop_bets_win_exactly_or_within_the_tolerance() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            pit_stops_tolerance: 1,
+            ..Default::default()
         });
+        let exact = Player::create("exact".into(), None);
+        let near = Player::create("near".into(), None);
+        let far = Player::create("far".into(), None);
+        for (player, count) in [(&exact, 2), (&near, 1), (&far, 4)] {
+            assert!(betting_table
+                .place(
+                    Bet::PitStops {
+                        driver: Driver::HAM,
+                        count
+                    },
+                    player
+                )
+                .is_ok());
+        }
+        assert!(betting_table
+            .place(
+                Bet::PitStops {
+                    driver: Driver::HAM,
+                    count: 3
+                },
+                &exact
+            )
+            .is_err());
+        assert!(betting_table
+            .place(
+                Bet::PitStops {
+                    driver: Driver::RUS,
+                    count: 2
+                },
+                &exact
+            )
+            .is_ok());
         betting_table.register_outcome(Outcome {
-            outcome: Bet::WillHaveSafetyCar(true),
-            reward: 500,
+            outcome: Bet::PitStops {
+                driver: Driver::HAM,
+                count: 2,
+            },
+            reward: 100,
+            registered_at: None,
         });
+
+        let scores = betting_table.results();
+        assert_eq!(scores.get(&exact), Some(&100));
+        assert_eq!(scores.get(&near), Some(&100));
+        assert_eq!(scores.get(&far), None);
+    }
+    #[test]
+    fn player_report_lists_the_bets_and_the_score() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), Some(Multiplier::X2));
+        for bet in [Bet::FastestLap(Driver::LEC), Bet::WillHaveSafetyCar(true)] {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
         betting_table.register_outcome(Outcome {
             outcome: Bet::FastestLap(Driver::LEC),
-            reward: 2500,
+            reward: 100,
+            registered_at: None,
         });
+
+        assert_eq!(
+            betting_table.player_report(&player),
+            "Bets of Nuyts (x2):\n- LEC fastest lap\n- safety car yes\nScore: 200"
+        );
+        assert_eq!(
+            betting_table.player_report(&Player::create("idle".into(), None)),
+            "idle placed no bets"
+        );
+    }
+    #[test]
+    fn a_losing_banker_wipes_out_the_slip() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        for bet in [Bet::FastestLap(Driver::LEC), Bet::WillHaveSafetyCar(true)] {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+        assert_eq!(
+            betting_table.set_banker(&player, &Bet::DoesNotFinish(Driver::VER)),
+            Err(BetError::BetNotFound(Bet::DoesNotFinish(Driver::VER)))
+        );
+        assert!(betting_table
+            .set_banker(&player, &Bet::WillHaveSafetyCar(true))
+            .is_ok());
         betting_table.register_outcome(Outcome {
-            outcome: Bet::DriverOfTheDay(Driver::LEC),
-            reward: 5000,
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
         });
+        betting_table.resolve_safety_car(false, 100);
+        assert_eq!(betting_table.results().get(&player), Some(&0));
 
-        let scores = betting_table.results();
-        assert_eq!(scores.get(&demi).unwrap(), &18000);
-        assert_eq!(scores.get(&michiel).unwrap(), &1000);
+        assert!(betting_table
+            .set_banker(&player, &Bet::FastestLap(Driver::LEC))
+            .is_ok());
+        assert_eq!(betting_table.results().get(&player), Some(&100));
+    }
+    #[test]
+    fn winnings_lost_to_a_banker_are_not_redistributed() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            max_winnings: Some(150),
+            ..Default::default()
+        });
+        let banked = Player::create("banked".into(), Some(Multiplier::X3));
+        let small = Player::create("small".into(), None);
+        for bet in [Bet::FastestLap(Driver::LEC), Bet::WillHaveSafetyCar(true)] {
+            assert!(betting_table.place(bet, &banked).is_ok());
+        }
+        assert!(betting_table
+            .set_banker(&banked, &Bet::WillHaveSafetyCar(true))
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &small)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+        betting_table.resolve_safety_car(false, 100);
+
+        // the 300 of banked are wiped out before the cap, so nothing is forfeited
+        let scores = betting_table.redistribute_forfeited();
+        assert_eq!(scores.get(&banked), Some(&0));
+        assert_eq!(scores.get(&small), Some(&100));
+    }
+    #[test]
+    fn projected_standings_leave_out_slips_of_a_banker_not_assumed_to_hit() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), None);
+        for bet in [
+            Bet::FastestLap(Driver::LEC),
+            Bet::DoesNotFinish(Driver::ALB),
+            Bet::WillHaveSafetyCar(true),
+        ] {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+        assert!(betting_table
+            .set_banker(&player, &Bet::WillHaveSafetyCar(true))
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        let retirement = HashMap::from([(Bet::DoesNotFinish(Driver::ALB), true)]);
+        assert_eq!(
+            betting_table.projected_standings(&retirement).get(&player),
+            Some(&0)
+        );
+
+        // with the banker assumed to hit the points already won count again
+        let both = HashMap::from([
+            (Bet::DoesNotFinish(Driver::ALB), true),
+            (Bet::WillHaveSafetyCar(true), true),
+        ]);
+        assert_eq!(
+            betting_table.projected_standings(&both).get(&player),
+            Some(&300)
+        );
+    }
+    #[test]
+    fn lead_changes_count_every_new_leader() {
+        let mut betting_table = BettingTable::new();
+        let anna = Player::create("anna".into(), None);
+        let bob = Player::create("bob".into(), None);
+        for bet in [Bet::FastestLap(Driver::VER), Bet::WillHaveSafetyCar(true)] {
+            assert!(betting_table.place(bet, &anna).is_ok());
+        }
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::LEC,
+            position: Position::new(1),
+        };
+        assert!(betting_table.place(winning_position.clone(), &bob).is_ok());
+        assert_eq!(betting_table.lead_changes(), 0);
+
+        // anna leads, bob takes over, anna takes it back and nobody scores the last one
+        for (outcome, reward) in [
+            (Bet::FastestLap(Driver::VER), 100),
+            (winning_position, 200),
+            (Bet::WillHaveSafetyCar(true), 200),
+            (Bet::DriverOfTheDay(Driver::ALB), 100),
+        ] {
+            betting_table.register_outcome(Outcome {
+                outcome,
+                reward,
+                registered_at: None,
+            });
+        }
+
+        assert_eq!(betting_table.lead_changes(), 2);
     }
 }
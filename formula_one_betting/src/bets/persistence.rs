@@ -0,0 +1,181 @@
+use super::{BettingTable, SCHEMA_VERSION};
+use serde::{
+    de::{Error as _, Unexpected},
+    Deserialize, Deserializer,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// Stored bytes that can't be turned back into a betting table
+#[derive(Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// There is not even a version header
+    Empty,
+    /// The bytes were written in a format this version can't read
+    UnsupportedVersion(u32),
+    /// The header is fine but the table itself is damaged
+    Corrupt,
+}
+
+impl Error for DecodeError {}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Empty => write!(f, "There is no betting table to decode"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "Betting tables in format version {version} are not supported"
+                )
+            }
+            DecodeError::Corrupt => write!(f, "The stored betting table is corrupt"),
+        }
+    }
+}
+
+/// Reads the schema version stored inside a table, rejecting any other version
+/// so tables stored in any serde format are checked, not only those of `to_bytes`
+pub(super) fn current_schema_version<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version != SCHEMA_VERSION {
+        return Err(D::Error::invalid_value(
+            Unexpected::Unsigned(version.into()),
+            &format!("schema version {SCHEMA_VERSION}").as_str(),
+        ));
+    }
+
+    Ok(version)
+}
+
+impl BettingTable {
+    /// Encodes the whole table in a compact binary format,
+    /// prefixed with the schema version in four little endian bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = SCHEMA_VERSION.to_le_bytes().to_vec();
+        bytes.extend(bincode::serialize(self).expect("A betting table can always be encoded"));
+
+        bytes
+    }
+    /// Decodes a table written by `to_bytes`, rejecting any other format version
+    pub fn from_bytes(bytes: &[u8]) -> Result<BettingTable, DecodeError> {
+        if bytes.len() < 4 {
+            return Err(DecodeError::Empty);
+        }
+        let (version, table) = bytes.split_at(4);
+        let version = u32::from_le_bytes([version[0], version[1], version[2], version[3]]);
+        if version != SCHEMA_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        bincode::deserialize(table).map_err(|_| DecodeError::Corrupt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DecodeError;
+    use crate::{
+        bets::{schema_version, Bet, BettingTable, Outcome},
+        player::{Multiplier, Player},
+        race::{Location, Position, Race},
+        teams::{Constructor, Driver},
+    };
+
+    #[test]
+    fn a_table_survives_a_round_trip() {
+        let mut betting_table = BettingTable::for_race(Race::new(Location::Monaco, 2022));
+        let player = Player::create("Nuyts".into(), Some(Multiplier::X2))
+            .with_favorite_team(Constructor::Ferrari);
+        let winning_position = Bet::FinishPosition {
+            driver: Driver::LEC,
+            position: Position::new(1),
+        };
+        assert!(betting_table
+            .place(winning_position.clone(), &player)
+            .is_ok());
+        assert!(betting_table
+            .place(
+                Bet::Parlay(vec![
+                    Bet::FastestLap(Driver::LEC),
+                    Bet::WillHaveSafetyCar(true)
+                ]),
+                &player
+            )
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: winning_position,
+            reward: 100,
+            registered_at: None,
+        });
+
+        let decoded = BettingTable::from_bytes(&betting_table.to_bytes()).unwrap();
+        assert_eq!(
+            decoded.get_bets_for(&player),
+            betting_table.get_bets_for(&player)
+        );
+        assert_eq!(decoded.outcomes, betting_table.outcomes);
+        assert_eq!(decoded.race, betting_table.race);
+        assert_eq!(decoded.results(), betting_table.results());
+    }
+    #[test]
+    fn other_format_versions_are_rejected() {
+        let mut bytes = BettingTable::new().to_bytes();
+        bytes[0] = 0;
+
+        assert_eq!(
+            BettingTable::from_bytes(&bytes).err(),
+            Some(DecodeError::UnsupportedVersion(0))
+        );
+        assert_eq!(
+            BettingTable::from_bytes(&[]).err(),
+            Some(DecodeError::Empty)
+        );
+        let mut damaged = schema_version().to_le_bytes().to_vec();
+        damaged.push(255);
+        assert_eq!(
+            BettingTable::from_bytes(&damaged).err(),
+            Some(DecodeError::Corrupt)
+        );
+    }
+    #[test]
+    fn stored_tables_carry_the_schema_version() {
+        let bytes = BettingTable::new().to_bytes();
+
+        assert_eq!(
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            schema_version()
+        );
+    }
+    #[test]
+    fn tables_of_another_embedded_schema_version_are_rejected() {
+        let bytes = BettingTable::new().to_bytes();
+        // the header is fine, but the version stored inside the table is not
+        let mut table = bytes[4..].to_vec();
+        table[..4].copy_from_slice(&0u32.to_le_bytes());
+
+        let error = bincode::deserialize::<BettingTable>(&table).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "invalid value: integer `0`, expected schema version {}",
+                schema_version()
+            )
+        );
+        let mut damaged = bytes[..4].to_vec();
+        damaged.extend(table);
+        assert_eq!(
+            BettingTable::from_bytes(&damaged).err(),
+            Some(DecodeError::Corrupt)
+        );
+    }
+    #[test]
+    fn schema_version_is_pinned() {
+        // bump SCHEMA_VERSION and this test whenever the stored format changes
+        assert_eq!(schema_version(), 8);
+    }
+}
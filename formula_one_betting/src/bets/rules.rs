@@ -0,0 +1,399 @@
+use super::{Bet, Outcome};
+use std::mem::discriminant;
+
+/// How a bet relates to an outcome about the same thing
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MatchKind {
+    /// The outcome is exactly what the bet predicted
+    Exact,
+    /// The bet predicted a count that is off by this much,
+    /// the pool tolerance decides whether it still wins
+    Off(u32),
+}
+
+/// Which other bets of its kind a bet rules out on the slip of a player
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ClashRule {
+    /// A single bet of this kind per player
+    OnePerPlayer,
+    /// Every driver and every position can be picked once per player
+    OnePerDriverAndPosition,
+    /// Every driver can be picked once per player
+    OnePerDriver,
+    /// Only the very same bet clashes, e.g. one per driver
+    OnePerSubject,
+    /// Only the very same bet clashes, as well as the bet it negates
+    ExcludesNegated,
+}
+
+impl Bet {
+    /// How this bet matches a single outcome, None when the outcome does not win it.
+    /// Bets settled by a combination of outcomes never match a single one
+    pub fn settles_against(&self, outcome: &Outcome) -> Option<MatchKind> {
+        match (self, &outcome.outcome) {
+            (Bet::FinishersCount(count), Bet::FinishersCount(finishers)) if count != finishers => {
+                Some(MatchKind::Off(count.abs_diff(*finishers).into()))
+            }
+            (Bet::FirstSafetyCarLap(lap), Bet::FirstSafetyCarLap(deployed)) if lap != deployed => {
+                Some(MatchKind::Off(lap.abs_diff(*deployed).into()))
+            }
+            (Bet::OvertakeCount(count), Bet::OvertakeCount(overtakes)) if count != overtakes => {
+                Some(MatchKind::Off(count.abs_diff(*overtakes)))
+            }
+            (
+                Bet::PitStops { driver, count },
+                Bet::PitStops {
+                    driver: stopped,
+                    count: stops,
+                },
+            ) if driver == stopped && count != stops => {
+                Some(MatchKind::Off(count.abs_diff(*stops).into()))
+            }
+            (bet, settled) if bet == settled => Some(MatchKind::Exact),
+            _ => None,
+        }
+    }
+    /// Whether a player can't have this bet next to the other one on a slip,
+    /// most bets allow a single pick of their kind
+    pub fn clashes_with(&self, other: &Bet) -> bool {
+        if let (Bet::Not(negated), bet) | (bet, Bet::Not(negated)) = (self, other) {
+            if **negated == *bet {
+                return true;
+            }
+        }
+        if discriminant(self) != discriminant(other) {
+            return false;
+        }
+
+        match self.clash_rule() {
+            ClashRule::OnePerPlayer => true,
+            ClashRule::OnePerDriverAndPosition => match (self, other) {
+                (
+                    Bet::FinishPosition { driver, position }
+                    | Bet::StartPosition { driver, position },
+                    Bet::FinishPosition {
+                        driver: other_driver,
+                        position: other_position,
+                    }
+                    | Bet::StartPosition {
+                        driver: other_driver,
+                        position: other_position,
+                    },
+                ) => driver == other_driver || position == other_position,
+                _ => false,
+            },
+            ClashRule::OnePerDriver => self.driver() == other.driver(),
+            ClashRule::OnePerSubject | ClashRule::ExcludesNegated => self == other,
+        }
+    }
+    /// Which other bets of its kind this bet clashes with
+    pub fn clash_rule(&self) -> ClashRule {
+        match self {
+            Bet::FinishPosition { .. } | Bet::StartPosition { .. } => {
+                ClashRule::OnePerDriverAndPosition
+            }
+            Bet::PitStops { .. } => ClashRule::OnePerDriver,
+            // The legs of a parlay are independent of the standalone bets of the player
+            Bet::DoesNotFinish(_)
+            | Bet::AnyDriverPenalized(_)
+            | Bet::ScoresPoints(_)
+            | Bet::BothFinish(_, _)
+            | Bet::BeatsPoleSitter(_)
+            | Bet::Parlay(_)
+            | Bet::Conditional { .. } => ClashRule::OnePerSubject,
+            Bet::Not(_) => ClashRule::ExcludesNegated,
+            _ => ClashRule::OnePerPlayer,
+        }
+    }
+    /// Whether both bets answer the same single pick question differently,
+    /// so at most one of them can win
+    pub fn hedges(&self, other: &Bet) -> bool {
+        match self {
+            Bet::FastestLap(_)
+            | Bet::DriverOfTheDay(_)
+            | Bet::WillHaveSafetyCar(_)
+            | Bet::FinishersCount(_)
+            | Bet::ExactDNFs(_)
+            | Bet::PolePosition(_)
+            | Bet::FirstRetirement(_)
+            | Bet::BestRookie(_)
+            | Bet::ConstructorOneTwo(_)
+            | Bet::FirstTeamToScore(_)
+            | Bet::BreaksTrackRecord(_)
+            | Bet::NumberOfPenalties(_)
+            | Bet::RaceIsWet(_)
+            | Bet::FirstSafetyCarLap(_)
+            | Bet::OvertakeCount(_) => discriminant(self) == discriminant(other) && self != other,
+            Bet::PitStops { driver, count } => matches!(
+                other,
+                Bet::PitStops { driver: other_driver, count: other_count }
+                    if driver == other_driver && count != other_count
+            ),
+            _ => false,
+        }
+    }
+    /// Whether the bet makes sense on its own, regardless of the rest of the slip
+    pub fn is_well_formed(&self) -> bool {
+        match self {
+            Bet::FinishersCount(count) => *count <= 20,
+            Bet::FirstSafetyCarLap(lap) => *lap > 0,
+            Bet::BothFinish(first, second) => first != second,
+            Bet::ExactDNFs(drivers) => !drivers.is_empty(),
+            Bet::Parlay(legs) => {
+                legs.len() >= 2
+                    && legs.iter().enumerate().all(|(index, leg)| {
+                        !leg.is_composite()
+                            && leg.is_well_formed()
+                            && !legs[..index]
+                                .iter()
+                                .any(|earlier| leg.clashes_with(earlier))
+                    })
+            }
+            Bet::Conditional { if_bet, then_bet } => {
+                !if_bet.is_composite()
+                    && !then_bet.is_composite()
+                    && if_bet.is_well_formed()
+                    && then_bet.is_well_formed()
+                    && !if_bet.clashes_with(then_bet)
+            }
+            Bet::Not(bet) => bet.is_settled_by_a_single_outcome() && bet.is_well_formed(),
+            _ => true,
+        }
+    }
+    /// Whether the outcome decides this bet one way or the other,
+    /// e.g. any classification of the driver decides a finish position
+    pub fn is_decided_by(&self, outcome: &Outcome) -> bool {
+        match (self, &outcome.outcome) {
+            (
+                Bet::FinishPosition { driver, .. } | Bet::DoesNotFinish(driver),
+                Bet::FinishPosition {
+                    driver: classified, ..
+                }
+                | Bet::DoesNotFinish(classified),
+            ) => driver == classified,
+            (Bet::FinishPosition { .. } | Bet::DoesNotFinish(_), _) => false,
+            // the penalty count comes with the final tally, nobody else was penalized
+            (Bet::AnyDriverPenalized(driver), Bet::AnyDriverPenalized(penalized)) => {
+                driver == penalized
+            }
+            (
+                Bet::PitStops { driver, .. },
+                Bet::PitStops {
+                    driver: stopped, ..
+                },
+            ) => driver == stopped,
+            (Bet::AnyDriverPenalized(_), decided) => {
+                matches!(decided, Bet::NumberOfPenalties(_))
+            }
+            (bet, decided) => discriminant(bet) == discriminant(decided),
+        }
+    }
+    /// Whether a single outcome settles the bet, rather than a combination of outcomes
+    fn is_settled_by_a_single_outcome(&self) -> bool {
+        matches!(
+            self,
+            Bet::FinishPosition { .. }
+                | Bet::DoesNotFinish(_)
+                | Bet::FastestLap(_)
+                | Bet::DriverOfTheDay(_)
+                | Bet::WillHaveSafetyCar(_)
+                | Bet::FinishersCount(_)
+                | Bet::PolePosition(_)
+                | Bet::FirstRetirement(_)
+                | Bet::BreaksTrackRecord(_)
+                | Bet::NumberOfPenalties(_)
+                | Bet::AnyDriverPenalized(_)
+                | Bet::RaceIsWet(_)
+                | Bet::FirstSafetyCarLap(_)
+                | Bet::OvertakeCount(_)
+                | Bet::PitStops { .. }
+        )
+    }
+    /// Whether the bet is built out of other bets
+    fn is_composite(&self) -> bool {
+        matches!(self, Bet::Parlay(_) | Bet::Conditional { .. })
+    }
+    /// The bets picked by placing this bet: the legs of a parlay,
+    /// both sides of a conditional bet or else the bet itself
+    pub(super) fn picks(&self) -> Vec<&Bet> {
+        match self {
+            Bet::Parlay(legs) => legs.iter().collect(),
+            Bet::Conditional { if_bet, then_bet } => vec![if_bet, then_bet],
+            bet => vec![bet],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClashRule, MatchKind};
+    use crate::{
+        bets::{Bet, Outcome},
+        race::Position,
+        teams::{Constructor, Driver, DriverSet},
+    };
+
+    fn every_kind_of_bet() -> Vec<Bet> {
+        vec![
+            Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(1),
+            },
+            Bet::StartPosition {
+                driver: Driver::VER,
+                position: Position::new(2),
+            },
+            Bet::DoesNotFinish(Driver::ALB),
+            Bet::FastestLap(Driver::LEC),
+            Bet::DriverOfTheDay(Driver::HAM),
+            Bet::WillHaveSafetyCar(true),
+            Bet::FinishersCount(18),
+            Bet::ExactDNFs([Driver::ALB].into_iter().collect::<DriverSet>()),
+            Bet::BothFinish(Driver::VER, Driver::HAM),
+            Bet::PolePosition(Driver::LEC),
+            Bet::BeatsPoleSitter(Driver::NOR),
+            Bet::FirstRetirement(Driver::MSC),
+            Bet::BestRookie(Driver::ZHO),
+            Bet::ConstructorOneTwo(Constructor::Ferrari),
+            Bet::FirstTeamToScore(Constructor::Haas),
+            Bet::Parlay(vec![
+                Bet::FastestLap(Driver::LEC),
+                Bet::PolePosition(Driver::LEC),
+            ]),
+            Bet::BreaksTrackRecord(false),
+            Bet::NumberOfPenalties(3),
+            Bet::AnyDriverPenalized(Driver::MAG),
+            Bet::ScoresPoints(Driver::ALB),
+            Bet::RaceIsWet(true),
+            Bet::FirstSafetyCarLap(12),
+            Bet::OvertakeCount(40),
+            Bet::PitStops {
+                driver: Driver::NOR,
+                count: 2,
+            },
+            Bet::Conditional {
+                if_bet: Box::new(Bet::WillHaveSafetyCar(true)),
+                then_bet: Box::new(Bet::DoesNotFinish(Driver::VER)),
+            },
+            Bet::Not(Box::new(Bet::FirstRetirement(Driver::STR))),
+        ]
+    }
+
+    #[test]
+    fn every_bet_settles_against_itself_and_clashes_with_itself() {
+        for bet in every_kind_of_bet() {
+            let outcome = Outcome {
+                outcome: bet.clone(),
+                reward: 100,
+                registered_at: None,
+            };
+            assert_eq!(bet.settles_against(&outcome), Some(MatchKind::Exact));
+            assert!(bet.is_settled_by(&outcome));
+            assert!(bet.is_well_formed());
+            assert!(bet.clashes_with(&bet));
+        }
+    }
+    #[test]
+    fn bets_of_a_different_kind_never_clash() {
+        let bets = every_kind_of_bet();
+
+        for (index, bet) in bets.iter().enumerate() {
+            for other in &bets[..index] {
+                assert!(!bet.clashes_with(other));
+                assert!(!other.clashes_with(bet));
+            }
+        }
+    }
+    #[test]
+    fn clash_rules_per_kind() {
+        let finish_position = |driver, position| Bet::FinishPosition {
+            driver,
+            position: Position::new(position),
+        };
+
+        assert!(finish_position(Driver::VER, 1).clashes_with(&finish_position(Driver::HAM, 1)));
+        assert!(finish_position(Driver::VER, 1).clashes_with(&finish_position(Driver::VER, 2)));
+        assert!(!finish_position(Driver::VER, 1).clashes_with(&finish_position(Driver::HAM, 2)));
+        assert!(!Bet::DoesNotFinish(Driver::ALB).clashes_with(&Bet::DoesNotFinish(Driver::MSC)));
+        assert!(Bet::FastestLap(Driver::LEC).clashes_with(&Bet::FastestLap(Driver::HAM)));
+        assert!(Bet::WillHaveSafetyCar(true).clashes_with(&Bet::WillHaveSafetyCar(false)));
+        assert!(!Bet::BothFinish(Driver::VER, Driver::HAM)
+            .clashes_with(&Bet::BothFinish(Driver::VER, Driver::ALB)));
+        assert!(!Bet::BothFinish(Driver::LEC, Driver::LEC).is_well_formed());
+        assert!(!Bet::FinishersCount(21).is_well_formed());
+    }
+    #[test]
+    fn validation_follows_the_declared_clash_rule() {
+        let finish_position = |driver, position| Bet::FinishPosition {
+            driver,
+            position: Position::new(position),
+        };
+        // pairs of the same kind that only differ in their pick
+        let pairs = [
+            (
+                finish_position(Driver::VER, 1),
+                finish_position(Driver::HAM, 2),
+            ),
+            (
+                Bet::DoesNotFinish(Driver::ALB),
+                Bet::DoesNotFinish(Driver::MSC),
+            ),
+            (Bet::FastestLap(Driver::LEC), Bet::FastestLap(Driver::HAM)),
+            (Bet::WillHaveSafetyCar(true), Bet::WillHaveSafetyCar(false)),
+            (Bet::FinishersCount(16), Bet::FinishersCount(18)),
+            (
+                Bet::Not(Box::new(Bet::FastestLap(Driver::LEC))),
+                Bet::Not(Box::new(Bet::FastestLap(Driver::HAM))),
+            ),
+        ];
+        let expected = [
+            ClashRule::OnePerDriverAndPosition,
+            ClashRule::OnePerSubject,
+            ClashRule::OnePerPlayer,
+            ClashRule::OnePerPlayer,
+            ClashRule::OnePerPlayer,
+            ClashRule::ExcludesNegated,
+        ];
+
+        for ((bet, other), rule) in pairs.iter().zip(expected) {
+            assert_eq!(bet.clash_rule(), rule);
+            assert_eq!(bet.clashes_with(other), rule == ClashRule::OnePerPlayer);
+        }
+        let negated = Bet::Not(Box::new(Bet::FastestLap(Driver::LEC)));
+        assert!(negated.clashes_with(&Bet::FastestLap(Driver::LEC)));
+        assert!(Bet::FastestLap(Driver::LEC).clashes_with(&negated));
+    }
+    #[test]
+    fn hedges_are_different_answers_to_a_single_pick_question() {
+        assert!(Bet::WillHaveSafetyCar(true).hedges(&Bet::WillHaveSafetyCar(false)));
+        assert!(Bet::FastestLap(Driver::LEC).hedges(&Bet::FastestLap(Driver::HAM)));
+        assert!(!Bet::FastestLap(Driver::LEC).hedges(&Bet::FastestLap(Driver::LEC)));
+        assert!(!Bet::FastestLap(Driver::LEC).hedges(&Bet::PolePosition(Driver::HAM)));
+        assert!(!Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(1),
+        }
+        .hedges(&Bet::FinishPosition {
+            driver: Driver::VER,
+            position: Position::new(2),
+        }));
+    }
+    #[test]
+    fn counts_report_how_far_off_they_are() {
+        let outcome = Outcome {
+            outcome: Bet::FinishersCount(16),
+            reward: 100,
+            registered_at: None,
+        };
+
+        assert_eq!(
+            Bet::FinishersCount(18).settles_against(&outcome),
+            Some(MatchKind::Off(2))
+        );
+        assert!(!Bet::FinishersCount(18).is_settled_by(&outcome));
+        assert_eq!(
+            Bet::DoesNotFinish(Driver::ALB).settles_against(&outcome),
+            None
+        );
+    }
+}
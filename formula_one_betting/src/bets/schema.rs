@@ -0,0 +1,181 @@
+use super::{Bet, BettingTable};
+use crate::{race::Position, teams::DriverSet};
+
+/// The outcomes a race needs before its standings are final
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RaceBetSchema {
+    /// How many cars have to be either classified or retired
+    pub finish_positions: u8,
+    pub fastest_lap: bool,
+    pub driver_of_the_day: bool,
+    pub safety_car: bool,
+}
+
+impl Default for RaceBetSchema {
+    /// A full grid of 20 cars, with every outcome that is decided in a race
+    fn default() -> Self {
+        Self {
+            finish_positions: 20,
+            fastest_lap: true,
+            driver_of_the_day: true,
+            safety_car: true,
+        }
+    }
+}
+
+/// Everything at least one placed bet is about, legs of composite bets included
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BetSubjects {
+    pub drivers: DriverSet,
+    /// Finish and start positions, in grid order
+    pub positions: Vec<Position>,
+    pub safety_car: bool,
+    pub track_record: bool,
+    pub wet_race: bool,
+}
+
+impl BetSubjects {
+    fn add(&mut self, bet: &Bet) {
+        match bet {
+            Bet::Parlay(legs) => legs.iter().for_each(|leg| self.add(leg)),
+            Bet::Conditional { if_bet, then_bet } => {
+                self.add(if_bet);
+                self.add(then_bet);
+            }
+            Bet::Not(bet) => self.add(bet),
+            Bet::FinishPosition { driver, position } | Bet::StartPosition { driver, position } => {
+                self.drivers.insert(*driver);
+                if !self.positions.contains(position) {
+                    self.positions.push(*position);
+                }
+            }
+            Bet::WillHaveSafetyCar(_) => self.safety_car = true,
+            Bet::BreaksTrackRecord(_) => self.track_record = true,
+            Bet::RaceIsWet(_) => self.wet_race = true,
+            bet => {
+                for driver in bet.drivers().drivers() {
+                    self.drivers.insert(driver);
+                }
+            }
+        }
+    }
+}
+
+impl BettingTable {
+    /// What the placed bets of all players are about, e.g. to know which outcomes
+    /// have to be registered
+    pub fn active_subjects(&self) -> BetSubjects {
+        let mut subjects = BetSubjects::default();
+        for bet in self.placed_bets.values().flatten() {
+            subjects.add(bet);
+        }
+        subjects.positions.sort();
+
+        subjects
+    }
+    /// Whether every outcome the schema needs is registered, so the standings are final
+    pub fn is_race_complete(&self, expected: &RaceBetSchema) -> bool {
+        let decided_cars: DriverSet = self
+            .outcomes
+            .iter()
+            .filter_map(|outcome| match outcome.outcome {
+                Bet::FinishPosition { driver, .. } | Bet::DoesNotFinish(driver) => Some(driver),
+                _ => None,
+            })
+            .collect();
+        let has_outcome = |is_kind: fn(&Bet) -> bool| {
+            self.outcomes
+                .iter()
+                .any(|outcome| is_kind(&outcome.outcome))
+        };
+
+        decided_cars.len() >= expected.finish_positions as usize
+            && (!expected.fastest_lap || has_outcome(|bet| matches!(bet, Bet::FastestLap(_))))
+            && (!expected.driver_of_the_day
+                || has_outcome(|bet| matches!(bet, Bet::DriverOfTheDay(_))))
+            && (!expected.safety_car || has_outcome(|bet| matches!(bet, Bet::WillHaveSafetyCar(_))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BetSubjects, RaceBetSchema};
+    use crate::{
+        bets::{Bet, BettingTable, Outcome},
+        player::Player,
+        race::Position,
+        teams::{Driver, DriverSet},
+    };
+
+    #[test]
+    fn a_race_is_complete_once_every_expected_outcome_is_in() {
+        let mut betting_table = BettingTable::new();
+        let mut classification = Driver::all().to_vec();
+        let retired = classification.pop().unwrap();
+        for outcome in Outcome::from_classification(&classification, 10) {
+            betting_table.register_outcome(outcome);
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+        betting_table.resolve_safety_car(false, 100);
+        assert!(!betting_table.is_race_complete(&RaceBetSchema::default()));
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::DoesNotFinish(retired),
+            reward: 100,
+            registered_at: None,
+        });
+        assert!(!betting_table.is_race_complete(&RaceBetSchema::default()));
+        assert!(betting_table.is_race_complete(&RaceBetSchema {
+            driver_of_the_day: false,
+            ..Default::default()
+        }));
+
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::DriverOfTheDay(Driver::ALB),
+            reward: 100,
+            registered_at: None,
+        });
+        assert!(betting_table.is_race_complete(&RaceBetSchema::default()));
+    }
+    #[test]
+    fn active_subjects_cover_every_placed_bet() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        for bet in [
+            Bet::FinishPosition {
+                driver: Driver::VER,
+                position: Position::new(3),
+            },
+            Bet::WillHaveSafetyCar(true),
+        ] {
+            assert!(betting_table.place(bet, &michiel).is_ok());
+        }
+        for bet in [
+            Bet::FinishPosition {
+                driver: Driver::HAM,
+                position: Position::new(1),
+            },
+            Bet::Parlay(vec![Bet::FastestLap(Driver::LEC), Bet::RaceIsWet(false)]),
+        ] {
+            assert!(betting_table.place(bet, &demi).is_ok());
+        }
+
+        assert_eq!(
+            betting_table.active_subjects(),
+            BetSubjects {
+                drivers: [Driver::VER, Driver::HAM, Driver::LEC]
+                    .into_iter()
+                    .collect::<DriverSet>(),
+                positions: vec![Position::new(1), Position::new(3)],
+                safety_car: true,
+                track_record: false,
+                wet_race: true,
+            }
+        );
+    }
+}
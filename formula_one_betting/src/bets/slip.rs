@@ -0,0 +1,211 @@
+use super::{errors::ParseError, Bet};
+use crate::{race::Position, teams::Driver};
+use std::fmt::{self, Display, Formatter};
+
+impl Bet {
+    /// Reads a slip written in shorthand, e.g. "VER P1, safety car yes, LEC fastest lap".
+    /// Bets are separated by commas and are not case sensitive, drivers go by their code:
+    /// - `VER P1`: finish position
+    /// - `VER DNF`, `VER first retirement`: does not finish, first to retire
+    /// - `VER fastest lap`, `VER DOTD`: fastest lap, driver of the day
+    /// - `VER pole`, `VER beats pole`: pole position, finishes ahead of the pole sitter
+    /// - `VER HAM finish`: both drivers finish
+    /// - `18 finishers`: number of classified cars
+    /// - `safety car yes`, `track record no`: yes or no questions
+    pub fn parse_slip(input: &str) -> Result<Vec<Bet>, ParseError> {
+        input
+            .split(',')
+            .map(str::trim)
+            .filter(|bet| !bet.is_empty())
+            .map(parse_bet)
+            .collect()
+    }
+}
+
+/// Writes a bet in the shorthand of a slip, bets `parse_slip` does not know
+/// are written in the same style
+impl Display for Bet {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Bet::FinishPosition { driver, position } => {
+                write!(f, "{driver:?} P{}", position.value())
+            }
+            Bet::StartPosition { driver, position } => {
+                write!(f, "{driver:?} starts P{}", position.value())
+            }
+            Bet::DoesNotFinish(driver) => write!(f, "{driver:?} DNF"),
+            Bet::FastestLap(driver) => write!(f, "{driver:?} fastest lap"),
+            Bet::DriverOfTheDay(driver) => write!(f, "{driver:?} DOTD"),
+            Bet::WillHaveSafetyCar(answer) => write!(f, "safety car {}", yes_or_no(*answer)),
+            Bet::FinishersCount(count) => write!(f, "{count} finishers"),
+            Bet::ExactDNFs(drivers) => {
+                write!(f, "exactly")?;
+                for driver in drivers.drivers() {
+                    write!(f, " {driver:?}")?;
+                }
+                write!(f, " DNF")
+            }
+            Bet::PolePosition(driver) => write!(f, "{driver:?} pole"),
+            Bet::BothFinish(first, second) => write!(f, "{first:?} {second:?} finish"),
+            Bet::BeatsPoleSitter(driver) => write!(f, "{driver:?} beats pole"),
+            Bet::FirstRetirement(driver) => write!(f, "{driver:?} first retirement"),
+            Bet::BestRookie(driver) => write!(f, "{driver:?} best rookie"),
+            Bet::ConstructorOneTwo(team) => write!(f, "{team:?} one two"),
+            Bet::FirstTeamToScore(team) => write!(f, "{team:?} scores first"),
+            Bet::Parlay(legs) => {
+                let legs: Vec<String> = legs.iter().map(Bet::to_string).collect();
+                write!(f, "parlay of {}", legs.join(" and "))
+            }
+            Bet::Conditional { if_bet, then_bet } => write!(f, "if {if_bet} then {then_bet}"),
+            Bet::Not(bet) => write!(f, "not {bet}"),
+            Bet::BreaksTrackRecord(answer) => write!(f, "track record {}", yes_or_no(*answer)),
+            Bet::NumberOfPenalties(count) => write!(f, "{count} penalties"),
+            Bet::AnyDriverPenalized(driver) => write!(f, "{driver:?} penalized"),
+            Bet::ScoresPoints(driver) => write!(f, "{driver:?} scores points"),
+            Bet::RaceIsWet(answer) => write!(f, "wet race {}", yes_or_no(*answer)),
+            Bet::FirstSafetyCarLap(lap) => write!(f, "first safety car on lap {lap}"),
+            Bet::OvertakeCount(count) => write!(f, "{count} overtakes"),
+            Bet::PitStops { driver, count } => write!(f, "{driver:?} {count} pit stops"),
+        }
+    }
+}
+
+fn yes_or_no(answer: bool) -> &'static str {
+    if answer {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn parse_bet(input: &str) -> Result<Bet, ParseError> {
+    let tokens: Vec<&str> = input.split_whitespace().collect();
+    let words: Vec<String> = tokens.iter().map(|token| token.to_lowercase()).collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    let bet = match words.as_slice() {
+        ["safety", "car", _] => Bet::WillHaveSafetyCar(parse_answer(tokens[2])?),
+        ["track", "record", _] => Bet::BreaksTrackRecord(parse_answer(tokens[2])?),
+        [_, "finishers"] => Bet::FinishersCount(
+            tokens[0]
+                .parse()
+                .ok()
+                .filter(|count| *count <= 20)
+                .ok_or_else(|| unknown(tokens[0]))?,
+        ),
+        [_, _, "finish"] => Bet::BothFinish(parse_driver(tokens[0])?, parse_driver(tokens[1])?),
+        [_, rest @ ..] => {
+            let driver = parse_driver(tokens[0])?;
+            match rest {
+                ["dnf"] => Bet::DoesNotFinish(driver),
+                ["first", "retirement"] => Bet::FirstRetirement(driver),
+                ["fastest", "lap"] => Bet::FastestLap(driver),
+                ["dotd"] => Bet::DriverOfTheDay(driver),
+                ["pole"] => Bet::PolePosition(driver),
+                ["beats", "pole"] => Bet::BeatsPoleSitter(driver),
+                [position] if position.starts_with('p') => Bet::FinishPosition {
+                    driver,
+                    position: parse_position(tokens[1])?,
+                },
+                _ => return Err(unknown(input)),
+            }
+        }
+        [] => return Err(unknown(input)),
+    };
+
+    Ok(bet)
+}
+
+fn parse_driver(token: &str) -> Result<Driver, ParseError> {
+    token.parse().map_err(|_| unknown(token))
+}
+
+fn parse_position(token: &str) -> Result<Position, ParseError> {
+    token[1..]
+        .parse()
+        .ok()
+        .filter(|position| (1..=20).contains(position))
+        .map(Position::new)
+        .ok_or_else(|| unknown(token))
+}
+
+fn parse_answer(token: &str) -> Result<bool, ParseError> {
+    match token.to_lowercase().as_str() {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(unknown(token)),
+    }
+}
+
+fn unknown(token: &str) -> ParseError {
+    ParseError {
+        token: token.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseError;
+    use crate::{bets::Bet, race::Position, teams::Driver};
+
+    #[test]
+    fn a_slip_parses_into_its_bets() {
+        assert_eq!(
+            Bet::parse_slip(
+                "VER P1, safety car yes, LEC fastest lap, ham alb finish, 17 finishers"
+            ),
+            Ok(vec![
+                Bet::FinishPosition {
+                    driver: Driver::VER,
+                    position: Position::new(1),
+                },
+                Bet::WillHaveSafetyCar(true),
+                Bet::FastestLap(Driver::LEC),
+                Bet::BothFinish(Driver::HAM, Driver::ALB),
+                Bet::FinishersCount(17),
+            ])
+        );
+        assert_eq!(Bet::parse_slip(""), Ok(vec![]));
+    }
+    #[test]
+    fn parsing_reports_the_offending_token() {
+        assert_eq!(
+            Bet::parse_slip("VER P1, XYZ DNF"),
+            Err(ParseError {
+                token: "XYZ".into()
+            })
+        );
+        assert_eq!(
+            Bet::parse_slip("VER P21"),
+            Err(ParseError {
+                token: "P21".into()
+            })
+        );
+        assert_eq!(
+            Bet::parse_slip("safety car maybe"),
+            Err(ParseError {
+                token: "maybe".into()
+            })
+        );
+        assert_eq!(
+            Bet::parse_slip("LEC slowest lap"),
+            Err(ParseError {
+                token: "LEC slowest lap".into()
+            })
+        );
+    }
+    #[test]
+    fn written_bets_parse_back() {
+        let slip = Bet::parse_slip(
+            "VER P1, safety car no, LEC fastest lap, HAM ALB finish, 17 finishers, ALO beats pole",
+        )
+        .unwrap();
+        let written: Vec<String> = slip.iter().map(Bet::to_string).collect();
+
+        assert_eq!(Bet::parse_slip(&written.join(", ")), Ok(slip));
+        assert_eq!(
+            Bet::Not(Box::new(Bet::DoesNotFinish(Driver::VER))).to_string(),
+            "not VER DNF"
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use super::{
+    errors::{InvalidProbability, ScoringError},
+    Bet, BettingTable, PARTS_PER_POINT,
+};
+use crate::{player::Player, teams::Constructor};
+use std::collections::HashMap;
+
+/// Summary of how the current scores are spread over the players
+#[derive(Debug, Default, PartialEq)]
+pub struct RewardStats {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub std_dev: f64,
+}
+
+impl BettingTable {
+    /// Statistics over the scores of every player, those who did not score yet included.
+    /// All zero when nobody scored yet.
+    /// Panics when a score overflows, see `try_reward_distribution` to handle that instead
+    pub fn reward_distribution(&self) -> RewardStats {
+        self.try_reward_distribution().expect("Scores fit in a u64")
+    }
+    /// Statistics over the current results, or an error when a score no longer fits in a u64
+    pub fn try_reward_distribution(&self) -> Result<RewardStats, ScoringError> {
+        let results = self.try_results()?;
+        let scores: Vec<u64> = self
+            .joined_players()
+            .map(|player| results.get(player).copied().unwrap_or(0))
+            .collect();
+        if scores.is_empty() {
+            return Ok(RewardStats::default());
+        }
+
+        let count = scores.len() as f64;
+        let mean = scores.iter().map(|score| *score as f64).sum::<f64>() / count;
+        let variance = scores
+            .iter()
+            .map(|score| (*score as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        Ok(RewardStats {
+            min: *scores.iter().min().unwrap_or(&0),
+            max: *scores.iter().max().unwrap_or(&0),
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+    /// The Gini coefficient of the scores of every player, those who did not score yet
+    /// included: 0 when everyone is level, towards 1 when a single player takes it all.
+    /// Zero for tables with fewer than two players or without any score.
+    /// Panics when a score overflows, see `try_standings_inequality` to handle that instead
+    pub fn standings_inequality(&self) -> f64 {
+        self.try_standings_inequality()
+            .expect("Scores fit in a u64")
+    }
+    /// The Gini coefficient of the scores, or an error when a score no longer fits in a u64
+    pub fn try_standings_inequality(&self) -> Result<f64, ScoringError> {
+        let results = self.try_results()?;
+        let scores: Vec<f64> = self
+            .joined_players()
+            .map(|player| results.get(player).copied().unwrap_or(0) as f64)
+            .collect();
+        let count = scores.len() as f64;
+        let total: f64 = scores.iter().sum();
+        if scores.len() < 2 || total == 0.0 {
+            return Ok(0.0);
+        }
+
+        let differences: f64 = scores
+            .iter()
+            .flat_map(|score| scores.iter().map(move |other| (score - other).abs()))
+            .sum();
+        // the mean absolute difference over twice the mean score
+        Ok(differences / (2.0 * count * total))
+    }
+    /// The reward that pays out exactly the total pot when this bet wins:
+    /// `total_pot / backers`, where the total pot is the stake of every placed bet,
+    /// including the stakes of bets placed with `place_with_stake`.
+    /// Zero when nobody backed the bet, since it then never pays out,
+    /// and u64::MAX when the pot per backer no longer fits in a u64
+    pub fn break_even_reward(&self, bet: &Bet) -> u64 {
+        let bet = bet.normalized();
+        let backers = self
+            .placed_bets
+            .values()
+            .filter(|bets| bets.contains(&bet))
+            .count() as u64;
+        if backers == 0 {
+            return 0;
+        }
+
+        let total_pot = self
+            .placed_bets
+            .iter()
+            .flat_map(|(id, bets)| bets.iter().map(|bet| self.stake_of(*id, bet)))
+            .try_fold(0u128, |pot, stake| pot.checked_add(u128::from(stake)));
+        total_pot
+            .and_then(|pot| u64::try_from(pot / u128::from(backers)).ok())
+            .unwrap_or(u64::MAX)
+    }
+    /// Sets pari-mutuel rewards on the registered outcomes: the pot is split equally over
+    /// the outcomes someone backed, and the share of an outcome equally over its backers,
+    /// so `reward = total_pot / (backed outcomes * backers)`, rounded down.
+    /// Long shots with few backers pay more, while paying every backer once never
+    /// exceeds the pot, before multipliers. Outcomes nobody backed are set to pay nothing
+    pub fn auto_balance_rewards(&mut self, total_pot: u64) {
+        let backers: Vec<u64> = self
+            .outcomes
+            .iter()
+            .map(|outcome| {
+                self.placed_bets
+                    .values()
+                    .filter(|bets| {
+                        bets.iter()
+                            .flat_map(Bet::picks)
+                            .any(|bet| self.is_winning_match(bet, outcome))
+                    })
+                    .count() as u64
+            })
+            .collect();
+        let backed_outcomes = backers.iter().filter(|backers| **backers > 0).count() as u64;
+
+        for (outcome, backers) in self.outcomes.iter_mut().zip(backers) {
+            outcome.reward = match backers {
+                0 => 0,
+                backers => total_pot / (backed_outcomes * backers),
+            };
+        }
+    }
+    /// What the slip of a player earns on average given the chance of every bet to hit,
+    /// the multiplier included. Bets without a probability are expected to lose
+    pub fn expected_value(
+        &self,
+        player: &Player,
+        probabilities: &HashMap<Bet, f64>,
+    ) -> Result<f64, InvalidProbability> {
+        let mut expected_value = 0.0;
+        for bet in self.get_bets_for(player) {
+            let probability = probabilities.get(&bet).copied().unwrap_or(0.0);
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(InvalidProbability { bet, probability });
+            }
+            let reward = self.projected_reward(&bet) as f64 / PARTS_PER_POINT as f64;
+            expected_value += reward * probability;
+        }
+
+        Ok(expected_value * player.multiplier.value() as f64)
+    }
+    /// How many placed bets refer to a driver of each team, a bet on both drivers
+    /// of a team counts once. Bets without a driver are left out
+    pub fn team_popularity(&self) -> HashMap<Constructor, usize> {
+        let mut popularity = HashMap::<Constructor, usize>::new();
+
+        for bet in self.placed_bets.values().flatten() {
+            let mut teams = Vec::<Constructor>::new();
+            for team in bet.drivers().drivers().iter().map(|driver| driver.team()) {
+                if !teams.contains(&team) {
+                    teams.push(team);
+                }
+            }
+            for team in teams {
+                *popularity.entry(team).or_insert(0) += 1;
+            }
+        }
+
+        popularity
+    }
+    /// How much a player follows the crowd: the average share of the players
+    /// who placed the same bets, the player included.
+    /// Close to 1 for a player only backing favorites, zero without bets
+    pub fn crowd_alignment(&self, player: &Player) -> f64 {
+        let bets = self.get_bets_for(player);
+        if bets.is_empty() {
+            return 0.0;
+        }
+
+        let players = self.player_count() as f64;
+        let total_popularity: f64 = bets
+            .iter()
+            .map(|bet| {
+                let backers = self
+                    .placed_bets
+                    .values()
+                    .filter(|slip| slip.contains(bet))
+                    .count();
+                backers as f64 / players
+            })
+            .sum();
+
+        total_popularity / bets.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RewardStats;
+    use crate::{
+        bets::{config::PoolConfig, errors::InvalidProbability, Bet, BettingTable, Outcome},
+        player::{Multiplier, Player},
+        teams::{Constructor, Driver},
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn balanced_rewards_stay_within_the_pot() {
+        let mut betting_table = BettingTable::new();
+        let favorite = Outcome {
+            outcome: Bet::FastestLap(Driver::VER),
+            reward: 100,
+            registered_at: None,
+        };
+        let long_shot = Outcome {
+            outcome: Bet::DoesNotFinish(Driver::ALB),
+            reward: 100,
+            registered_at: None,
+        };
+        let unbacked = Outcome {
+            outcome: Bet::DriverOfTheDay(Driver::MAG),
+            reward: 100,
+            registered_at: None,
+        };
+        for name in ["anna", "bob", "carl"] {
+            let player = Player::create(name.into(), None);
+            let bet = match name {
+                "carl" => Bet::DoesNotFinish(Driver::ALB),
+                _ => Bet::FastestLap(Driver::VER),
+            };
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+        for outcome in [favorite, long_shot, unbacked] {
+            betting_table.register_outcome(outcome);
+        }
+
+        betting_table.auto_balance_rewards(600);
+
+        let rewards: Vec<u64> = betting_table
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.reward)
+            .collect();
+        assert_eq!(rewards, vec![150, 300, 0]);
+        assert!(rewards.iter().sum::<u64>() <= 600);
+        assert!(betting_table.results().values().sum::<u64>() <= 600);
+    }
+    #[test]
+    fn team_popularity_counts_the_bets_on_either_driver() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        for bet in [
+            Bet::FastestLap(Driver::VER),
+            Bet::DoesNotFinish(Driver::PER),
+            Bet::WillHaveSafetyCar(true),
+        ] {
+            assert!(betting_table.place(bet, &michiel).is_ok());
+        }
+        for bet in [
+            Bet::BothFinish(Driver::VER, Driver::PER),
+            Bet::BothFinish(Driver::VER, Driver::LEC),
+        ] {
+            assert!(betting_table.place(bet, &demi).is_ok());
+        }
+
+        assert_eq!(
+            betting_table.team_popularity(),
+            HashMap::from([(Constructor::RedBull, 4), (Constructor::Ferrari, 1)])
+        );
+    }
+    #[test]
+    fn reward_distribution_of_an_empty_table_is_zero() {
+        assert_eq!(
+            BettingTable::new().reward_distribution(),
+            RewardStats::default()
+        );
+    }
+    #[test]
+    fn reward_distribution_over_known_scores() {
+        let mut betting_table = BettingTable::new();
+        let players = [
+            Player::create("michiel".into(), None),
+            Player::create("demi".into(), Some(Multiplier::X2)),
+            Player::create("nuyts".into(), Some(Multiplier::X3)),
+        ];
+        for player in &players {
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::LEC), player)
+                .is_ok());
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        // scores are 100, 200 and 300
+        let stats = betting_table.reward_distribution();
+        assert_eq!(stats.min, 100);
+        assert_eq!(stats.max, 300);
+        assert_eq!(stats.mean, 200.0);
+        assert!((stats.std_dev - 81.6497).abs() < 0.001);
+    }
+    #[test]
+    fn reward_distribution_counts_players_who_scored_nothing() {
+        let mut betting_table = BettingTable::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &michiel)
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::HAM), &demi)
+            .is_ok());
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        // scores are 100 and 0
+        let stats = betting_table.reward_distribution();
+        assert_eq!(stats.min, 0);
+        assert_eq!(stats.max, 100);
+        assert_eq!(stats.mean, 50.0);
+        assert_eq!(stats.std_dev, 50.0);
+    }
+    #[test]
+    fn break_even_reward_splits_the_pot_over_the_backers() {
+        let mut betting_table = BettingTable::with_config(PoolConfig {
+            stake_per_bet: 10,
+            ..Default::default()
+        });
+        let players = [
+            Player::create("michiel".into(), None),
+            Player::create("demi".into(), None),
+            Player::create("nuyts".into(), None),
+        ];
+        for player in &players {
+            assert!(betting_table
+                .place(Bet::WillHaveSafetyCar(true), player)
+                .is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &players[0])
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::LEC), &players[1])
+            .is_ok());
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::HAM), &players[2])
+            .is_ok());
+
+        // 6 bets at a stake of 10 make a pot of 60
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::WillHaveSafetyCar(true)),
+            20
+        );
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::FastestLap(Driver::LEC)),
+            30
+        );
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::FastestLap(Driver::HAM)),
+            60
+        );
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::FastestLap(Driver::VER)),
+            0
+        );
+
+        // uneven stakes count in full: the pot grows to 60 + 40 = 100
+        assert!(betting_table
+            .place_with_stake(Bet::PolePosition(Driver::VER), &players[0], 40)
+            .is_ok());
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::WillHaveSafetyCar(true)),
+            33
+        );
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::FastestLap(Driver::LEC)),
+            50
+        );
+        assert_eq!(
+            betting_table.break_even_reward(&Bet::PolePosition(Driver::VER)),
+            100
+        );
+    }
+    #[test]
+    fn crowd_alignment_separates_chalk_from_contrarians() {
+        let mut betting_table = BettingTable::new();
+        let chalk = Player::create("chalk".into(), None);
+        let contrarian = Player::create("contrarian".into(), None);
+        let follower = Player::create("follower".into(), None);
+        for player in [&chalk, &contrarian, &follower] {
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::VER), player)
+                .is_ok());
+        }
+        assert!(betting_table
+            .place(Bet::DoesNotFinish(Driver::ALB), &contrarian)
+            .is_ok());
+
+        assert_eq!(betting_table.crowd_alignment(&chalk), 1.0);
+        assert_eq!(betting_table.crowd_alignment(&contrarian), 2.0 / 3.0);
+        assert_eq!(
+            betting_table.crowd_alignment(&Player::create("idle".into(), None)),
+            0.0
+        );
+    }
+    #[test]
+    fn expected_value_weighs_every_reward_by_its_probability() {
+        let mut betting_table = BettingTable::new();
+        let player = Player::create("Nuyts".into(), Some(Multiplier::X2));
+        let both_finish = Bet::BothFinish(Driver::VER, Driver::HAM);
+        for bet in [
+            Bet::FastestLap(Driver::LEC),
+            both_finish.clone(),
+            Bet::DoesNotFinish(Driver::ALB),
+        ] {
+            assert!(betting_table.place(bet, &player).is_ok());
+        }
+
+        // (100 * 0.25 + 200 * 0.5 + nothing for the bet without a probability) * 2
+        let probabilities = HashMap::from([
+            (Bet::FastestLap(Driver::LEC), 0.25),
+            (both_finish.clone(), 0.5),
+        ]);
+        assert_eq!(
+            betting_table.expected_value(&player, &probabilities),
+            Ok(250.0)
+        );
+
+        let probabilities = HashMap::from([(both_finish.clone(), 1.5)]);
+        assert_eq!(
+            betting_table.expected_value(&player, &probabilities),
+            Err(InvalidProbability {
+                bet: both_finish,
+                probability: 1.5
+            })
+        );
+    }
+    #[test]
+    fn standings_inequality_is_the_gini_coefficient() {
+        let mut betting_table = BettingTable::new();
+        assert_eq!(betting_table.standings_inequality(), 0.0);
+
+        let players = [
+            Player::create("michiel".into(), None),
+            Player::create("demi".into(), Some(Multiplier::X2)),
+            Player::create("nuyts".into(), Some(Multiplier::X3)),
+        ];
+        for player in &players {
+            assert!(betting_table
+                .place(Bet::FastestLap(Driver::LEC), player)
+                .is_ok());
+        }
+        betting_table.register_outcome(Outcome {
+            outcome: Bet::FastestLap(Driver::LEC),
+            reward: 100,
+            registered_at: None,
+        });
+
+        // scores of 100, 200 and 300 differ by 800 over all pairs, over 2 * 3 * 600
+        assert!((betting_table.standings_inequality() - 2.0 / 9.0).abs() < 1e-9);
+
+        let idle = Player::create("idle".into(), None);
+        assert!(betting_table
+            .place(Bet::FastestLap(Driver::VER), &idle)
+            .is_ok());
+        // 100, 200, 300 and 0 differ by 2000 over all pairs, over 2 * 4 * 600
+        assert!((betting_table.standings_inequality() - 5.0 / 12.0).abs() < 1e-9);
+    }
+}
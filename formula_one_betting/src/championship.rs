@@ -0,0 +1,181 @@
+use crate::{
+    player::Player,
+    teams::{Constructor, Driver},
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem::discriminant,
+};
+
+/// Season long things a player can bet on
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ChampionshipBet {
+    /// Which driver wins the drivers' championship
+    DriversChampion(Driver),
+    /// Which team wins the constructors' championship
+    ConstructorsChampion(Constructor),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ClashesWithExistingPick {
+    pub existing_pick: ChampionshipBet,
+}
+
+impl Error for ClashesWithExistingPick {}
+
+impl Display for ClashesWithExistingPick {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "This player already picked a champion for this title")
+    }
+}
+
+/// The score of a player no longer fits in a whole number of points
+#[derive(Debug, Eq, PartialEq)]
+pub struct ChampionshipOverflow {
+    pub player: Player,
+}
+
+impl Error for ChampionshipOverflow {}
+
+impl Display for ChampionshipOverflow {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The championship score of {} is too large to count",
+            self.player.name
+        )
+    }
+}
+
+/// Betting table for a whole season, complementing the per race BettingTable.
+/// Scored once the champions are known at the end of the season
+#[derive(Default)]
+pub struct ChampionshipPool {
+    /// The champion picks indexed by player
+    picks: HashMap<Player, Vec<ChampionshipBet>>,
+    /// The crowned champions with the reward for picking them
+    champions: Vec<(ChampionshipBet, u64)>,
+}
+
+impl ChampionshipPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Places a champion pick, each player gets one pick per title
+    pub fn place(
+        &mut self,
+        bet: ChampionshipBet,
+        player: &Player,
+    ) -> Result<ChampionshipBet, ClashesWithExistingPick> {
+        let picks = self.picks.entry(player.clone()).or_default();
+        if let Some(existing_pick) = picks
+            .iter()
+            .find(|pick| discriminant(*pick) == discriminant(&bet))
+        {
+            return Err(ClashesWithExistingPick {
+                existing_pick: *existing_pick,
+            });
+        }
+        picks.push(bet);
+
+        Ok(bet)
+    }
+    /// Crowns the champion of a title, replacing an earlier resolution of the same title
+    pub fn resolve(&mut self, winner: ChampionshipBet, reward: u64) {
+        self.champions
+            .retain(|(champion, _)| discriminant(champion) != discriminant(&winner));
+        self.champions.push((winner, reward));
+    }
+    /// Scores of all players with a correct pick, multipliers included.
+    /// Panics when a score overflows, see `try_results` to handle that instead
+    pub fn results(&self) -> HashMap<Player, u64> {
+        self.try_results().expect("Scores fit in a u64")
+    }
+    /// Scores of all players with a correct pick, or an error when a score
+    /// no longer fits in a u64
+    pub fn try_results(&self) -> Result<HashMap<Player, u64>, ChampionshipOverflow> {
+        let mut scores = HashMap::<Player, u64>::new();
+
+        for (champion, reward) in &self.champions {
+            for (player, picks) in &self.picks {
+                if picks.contains(champion) {
+                    let overflow = || ChampionshipOverflow {
+                        player: player.clone(),
+                    };
+                    let score = scores.entry(player.clone()).or_insert(0);
+                    *score = reward
+                        .checked_mul(player.multiplier.value())
+                        .and_then(|reward| score.checked_add(reward))
+                        .ok_or_else(overflow)?;
+                }
+            }
+        }
+
+        Ok(scores)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChampionshipBet, ChampionshipOverflow, ChampionshipPool, ClashesWithExistingPick};
+    use crate::{
+        player::{Multiplier, Player},
+        teams::{Constructor, Driver},
+    };
+
+    #[test]
+    fn one_champion_pick_per_title() {
+        let mut pool = ChampionshipPool::new();
+        let player = Player::create("Nuyts".into(), None);
+
+        let pick = ChampionshipBet::DriversChampion(Driver::VER);
+        assert!(pool.place(pick, &player).is_ok());
+        assert!(pool
+            .place(
+                ChampionshipBet::ConstructorsChampion(Constructor::RedBull),
+                &player
+            )
+            .is_ok());
+        assert_eq!(
+            pool.place(ChampionshipBet::DriversChampion(Driver::LEC), &player),
+            Err(ClashesWithExistingPick {
+                existing_pick: pick
+            })
+        );
+    }
+    #[test]
+    fn resolving_the_drivers_champion_scores_correct_picks() {
+        let mut pool = ChampionshipPool::new();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), Some(Multiplier::X2));
+
+        assert!(pool
+            .place(ChampionshipBet::DriversChampion(Driver::VER), &michiel)
+            .is_ok());
+        assert!(pool
+            .place(ChampionshipBet::DriversChampion(Driver::LEC), &demi)
+            .is_ok());
+        pool.resolve(ChampionshipBet::DriversChampion(Driver::VER), 5000);
+
+        let scores = pool.results();
+        assert_eq!(scores.get(&michiel).unwrap(), &5000);
+        assert_eq!(scores.get(&demi), None);
+    }
+    #[test]
+    fn overflowing_championship_scores_are_an_error() {
+        let mut pool = ChampionshipPool::new();
+        let demi = Player::create("demi".into(), Some(Multiplier::X2));
+
+        assert!(pool
+            .place(ChampionshipBet::DriversChampion(Driver::VER), &demi)
+            .is_ok());
+        pool.resolve(ChampionshipBet::DriversChampion(Driver::VER), u64::MAX);
+
+        assert_eq!(
+            pool.try_results(),
+            Err(ChampionshipOverflow { player: demi })
+        );
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code, clippy::upper_case_acronyms)]
 
 mod bets;
+mod championship;
+mod odds;
 mod player;
 mod race;
 mod teams;
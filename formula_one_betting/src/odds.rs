@@ -0,0 +1,95 @@
+/// The largest denominator tried when writing decimal odds as a fraction
+const MAX_DENOMINATOR: u64 = 100;
+
+/// Writes decimal odds as fractional odds, the profit over the stake in lowest
+/// terms, e.g. 2.75 becomes "7/4". Odds that are no simple fraction get the closest
+/// fraction with a denominator up to 100. None for odds below 1
+pub fn decimal_to_fractional(decimal: f64) -> Option<String> {
+    if !decimal.is_finite() || decimal < 1.0 {
+        return None;
+    }
+
+    let profit = decimal - 1.0;
+    let (mut numerator, mut denominator) = (1..=MAX_DENOMINATOR)
+        .map(|denominator| ((profit * denominator as f64).round() as u64, denominator))
+        .min_by(
+            |(numerator, denominator), (other_numerator, other_denominator)| {
+                let error = (*numerator as f64 / *denominator as f64 - profit).abs();
+                let other_error =
+                    (*other_numerator as f64 / *other_denominator as f64 - profit).abs();
+                error.total_cmp(&other_error)
+            },
+        )?;
+    if numerator == 0 {
+        denominator = 1;
+    }
+    let divisor = gcd(numerator, denominator);
+    numerator /= divisor;
+    denominator /= divisor;
+
+    Some(format!("{numerator}/{denominator}"))
+}
+
+/// Reads fractional odds like "5/1" as decimal odds, the stake included, e.g. 6.0.
+/// None when it is not a fraction of whole numbers with a denominator above zero
+pub fn fractional_to_decimal(fractional: &str) -> Option<f64> {
+    let (numerator, denominator) = fractional.split_once('/')?;
+    let numerator: u64 = numerator.trim().parse().ok()?;
+    let denominator: u64 = denominator.trim().parse().ok()?;
+    if denominator == 0 {
+        return None;
+    }
+
+    Some(1.0 + numerator as f64 / denominator as f64)
+}
+
+/// The chance of winning the decimal odds imply, e.g. 0.25 for 4.0.
+/// None for odds below 1
+pub fn to_implied_probability(decimal: f64) -> Option<f64> {
+    (decimal.is_finite() && decimal >= 1.0).then(|| 1.0 / decimal)
+}
+
+fn gcd(first: u64, second: u64) -> u64 {
+    match second {
+        0 => first.max(1),
+        _ => gcd(second, first % second),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decimal_to_fractional, fractional_to_decimal, to_implied_probability};
+
+    #[test]
+    fn known_odds_round_trip() {
+        for (decimal, fractional) in [
+            (6.0, "5/1"),
+            (2.0, "1/1"),
+            (1.5, "1/2"),
+            (2.75, "7/4"),
+            (4.0 / 3.0, "1/3"),
+            (1.0, "0/1"),
+        ] {
+            assert_eq!(decimal_to_fractional(decimal).as_deref(), Some(fractional));
+            let converted = fractional_to_decimal(fractional).unwrap();
+            assert!((converted - decimal).abs() < 1e-9);
+        }
+        assert_eq!(fractional_to_decimal("10/4"), Some(3.5));
+    }
+    #[test]
+    fn odds_imply_a_probability() {
+        assert_eq!(to_implied_probability(4.0), Some(0.25));
+        assert_eq!(to_implied_probability(1.0), Some(1.0));
+        let probability = to_implied_probability(fractional_to_decimal("5/1").unwrap()).unwrap();
+        assert!((probability - 1.0 / 6.0).abs() < 1e-9);
+    }
+    #[test]
+    fn malformed_odds_are_rejected() {
+        assert_eq!(decimal_to_fractional(0.5), None);
+        assert_eq!(decimal_to_fractional(f64::NAN), None);
+        assert_eq!(to_implied_probability(0.0), None);
+        assert_eq!(fractional_to_decimal("5/0"), None);
+        assert_eq!(fractional_to_decimal("5-1"), None);
+        assert_eq!(fractional_to_decimal("five/1"), None);
+    }
+}
@@ -1,14 +1,137 @@
+use crate::teams::Constructor;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub name: String,
-    pub multiplier: u64, // x3, x5, ...
+    pub multiplier: Multiplier,
+    pub favorite_team: Option<Constructor>,
 }
 
 impl Player {
-    pub fn create(name: String, multiplier: Option<u64>) -> Self {
+    pub fn create(name: String, multiplier: Option<Multiplier>) -> Self {
         Self {
             name,
-            multiplier: multiplier.unwrap_or(1),
+            multiplier: multiplier.unwrap_or(Multiplier::X1),
+            favorite_team: None,
+        }
+    }
+    /// Registers the Constructors Team this player supports
+    pub fn with_favorite_team(mut self, team: Constructor) -> Self {
+        self.favorite_team = Some(team);
+        self
+    }
+}
+
+/// Compact handle of a player within a single table, cheap to store next to every bet
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PlayerId(u32);
+
+/// Hands out one id per distinct player and maps those ids back to the player
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PlayerInterner {
+    players: Vec<Player>,
+    ids: HashMap<Player, PlayerId>,
+}
+
+impl PlayerInterner {
+    /// The id of the player, handing out a new one the first time the player is seen
+    pub(crate) fn intern(&mut self, player: &Player) -> PlayerId {
+        if let Some(id) = self.ids.get(player) {
+            return *id;
         }
+
+        let id = PlayerId(self.players.len() as u32);
+        self.players.push(player.clone());
+        self.ids.insert(player.clone(), id);
+
+        id
+    }
+    /// The id of the player, if it was ever interned
+    pub(crate) fn id_of(&self, player: &Player) -> Option<PlayerId> {
+        self.ids.get(player).copied()
+    }
+    pub(crate) fn player(&self, id: PlayerId) -> &Player {
+        &self.players[id.0 as usize]
+    }
+    /// Forgets the id of the player, interning the player again hands out a new one.
+    /// The slot stays taken so the ids of other players keep pointing at them
+    pub(crate) fn forget(&mut self, id: PlayerId) {
+        self.ids.remove(&self.players[id.0 as usize]);
+    }
+}
+
+/// Factor applied on the score of a player, only x1, x2, x3, x5 and x10 exist
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct Multiplier(u64);
+
+impl Multiplier {
+    pub const X1: Multiplier = Multiplier(1);
+    pub const X2: Multiplier = Multiplier(2);
+    pub const X3: Multiplier = Multiplier(3);
+    pub const X5: Multiplier = Multiplier(5);
+    pub const X10: Multiplier = Multiplier(10);
+
+    pub fn new(multiplier: u64) -> Result<Self, InvalidMultiplier> {
+        match multiplier {
+            1 | 2 | 3 | 5 | 10 => Ok(Self(multiplier)),
+            _ => Err(InvalidMultiplier { multiplier }),
+        }
+    }
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct InvalidMultiplier {
+    pub multiplier: u64,
+}
+
+impl Error for InvalidMultiplier {}
+
+impl Display for InvalidMultiplier {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "x{} is not an allowed multiplier", self.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InvalidMultiplier, Multiplier, Player, PlayerInterner};
+
+    #[test]
+    fn only_allowed_multipliers_can_be_created() {
+        assert_eq!(Multiplier::new(0), Err(InvalidMultiplier { multiplier: 0 }));
+        assert_eq!(Multiplier::new(4), Err(InvalidMultiplier { multiplier: 4 }));
+        assert_eq!(Multiplier::new(3), Ok(Multiplier::X3));
+        assert_eq!(
+            Multiplier::new(5).map(|multiplier| multiplier.value()),
+            Ok(5)
+        );
+    }
+    #[test]
+    fn repeated_players_intern_to_the_same_id() {
+        let mut interner = PlayerInterner::default();
+        let michiel = Player::create("michiel".into(), None);
+        let demi = Player::create("demi".into(), None);
+
+        let id = interner.intern(&michiel);
+        assert_ne!(interner.intern(&demi), id);
+        assert_eq!(interner.intern(&michiel.clone()), id);
+        assert_eq!(interner.id_of(&michiel), Some(id));
+        assert_eq!(interner.player(id), &michiel);
+        assert_eq!(
+            interner.id_of(&Player::create("michiel".into(), Some(Multiplier::X2))),
+            None
+        );
     }
 }
@@ -1,10 +1,112 @@
+use crate::teams::{full_roster, Driver, DriverSet};
+
 /// Race information
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct Race {
     location: Location,
     season: u16, // 2022, 2023, ...
+    grid_size: u8,
+    /// The starting order, from pole onwards, empty until it is known
+    grid: Vec<Driver>,
+}
+
+impl Race {
+    /// A race with a full grid of 20 cars
+    pub fn new(location: Location, season: u16) -> Self {
+        Self {
+            location,
+            season,
+            grid_size: 20,
+            grid: Vec::new(),
+        }
+    }
+    /// Changes how many cars take the start, at most 20
+    pub fn with_grid_size(mut self, grid_size: u8) -> Self {
+        self.grid_size = grid_size.min(20);
+        self
+    }
+    pub fn grid_size(&self) -> u8 {
+        self.grid_size
+    }
+    pub fn season(&self) -> u16 {
+        self.season
+    }
+    /// Sets the starting order after qualifying and penalties, from pole onwards.
+    /// Drivers beyond the grid size don't take the start
+    pub fn set_grid(&mut self, grid: Vec<Driver>) {
+        self.grid = grid;
+        self.grid.truncate(self.grid_size as usize);
+    }
+    /// Drops a driver back the given number of grid slots, everyone in between moves up.
+    /// A penalty past the back of the grid starts the driver last
+    pub fn apply_grid_penalty(&mut self, driver: Driver, places: u8) {
+        let Some(index) = self.grid.iter().position(|starter| *starter == driver) else {
+            return;
+        };
+        let penalized = self.grid.remove(index);
+        let slot = (index + places as usize).min(self.grid.len());
+        self.grid.insert(slot, penalized);
+    }
+    /// The grid slot the driver starts from, None when the grid is not known
+    /// or the driver does not start
+    pub fn start_position_of(&self, driver: Driver) -> Option<Position> {
+        self.grid
+            .iter()
+            .position(|starter| *starter == driver)
+            .map(|index| Position::new(index as u8 + 1))
+    }
+}
+
+/// A full field of 20 cars, every driver of the roster in a slot of their own
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid(Vec<(Position, Driver)>);
+
+impl Grid {
+    /// Builds the grid from the order of the field, from pole onwards.
+    /// None unless every driver of the roster takes exactly one slot
+    pub fn new(order: Vec<Driver>) -> Option<Self> {
+        let mut roster = DriverSet::default();
+        for (first, second) in full_roster().into_values() {
+            roster.insert(first);
+            roster.insert(second);
+        }
+        let mut seen = DriverSet::default();
+        for driver in &order {
+            if !roster.contains(*driver) || seen.contains(*driver) {
+                return None;
+            }
+            seen.insert(*driver);
+        }
+        if seen != roster {
+            return None;
+        }
+
+        Some(Self(
+            order
+                .into_iter()
+                .enumerate()
+                .map(|(index, driver)| (Position::new(index as u8 + 1), driver))
+                .collect(),
+        ))
+    }
+    pub fn driver_at(&self, position: Position) -> Option<Driver> {
+        self.0
+            .iter()
+            .find(|(slot, _)| *slot == position)
+            .map(|(_, driver)| *driver)
+    }
+    pub fn position_of(&self, driver: Driver) -> Option<Position> {
+        self.0
+            .iter()
+            .find(|(_, occupant)| *occupant == driver)
+            .map(|(position, _)| *position)
+    }
 }
 
 /// Country location of a Grand Prix race
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub enum Location {
     Spain,
     Bahrain,
@@ -28,8 +130,57 @@ pub enum Location {
     Netherlands,
 }
 
-/// Position on the race grid, always from 1 up to 20
+impl Location {
+    /// The all-time race lap record of the circuit in seconds, None when the circuit has none yet
+    pub fn lap_record(&self) -> Option<f64> {
+        let record = match self {
+            Location::Spain => 78.149,
+            Location::Bahrain => 91.447,
+            Location::SaudiArabia => 90.734,
+            Location::Australia => 80.260,
+            Location::Italy => 81.046,
+            Location::Monaco => 72.909,
+            Location::Azerbaijan => 103.009,
+            Location::Canada => 73.078,
+            Location::UK => 87.097,
+            Location::Austria => 65.619,
+            Location::France => 92.740,
+            Location::Hungary => 76.627,
+            Location::Singapore => 101.905,
+            Location::Japan => 90.983,
+            Location::Mexico => 77.774,
+            Location::Brazil => 70.540,
+            Location::AbuDhabi => 86.103,
+            Location::USA => 96.169,
+            Location::Belgium => 106.286,
+            Location::Netherlands => 71.097,
+        };
+
+        Some(record)
+    }
+}
+
+/// How a driver ended the race
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DriverStatus {
+    Finished,
+    /// Did not finish
+    DNF,
+    /// Disqualified
+    DSQ,
+}
+
+/// The end result of a single driver, only finishers have a position
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct DriverResult {
+    pub driver: Driver,
+    pub status: DriverStatus,
+    pub position: Option<Position>,
+}
+
+/// Position on the race grid, always from 1 up to 20
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position(u8);
 
 impl Position {
@@ -39,4 +190,62 @@ impl Position {
             _ => panic!("Wrong input for position"),
         }
     }
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+    /// Whether a finish in this position scores championship points, the top 10
+    pub fn points_paying(&self) -> bool {
+        self.0 <= 10
+    }
+}
+
+/// Only generates positions on the grid, 1 up to 20
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Position {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Position::new(u.int_in_range(1..=20)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, Location, Position, Race};
+    use crate::teams::Driver;
+
+    #[test]
+    fn grid_is_looked_up_both_ways() {
+        let grid = Grid::new(Driver::all().to_vec()).unwrap();
+
+        assert_eq!(grid.driver_at(Position::new(1)), Some(Driver::VER));
+        assert_eq!(grid.driver_at(Position::new(20)), Some(Driver::ALB));
+        assert_eq!(grid.position_of(Driver::LEC), Some(Position::new(3)));
+    }
+    #[test]
+    fn grid_has_to_hold_every_driver_once() {
+        let mut order = Driver::all().to_vec();
+        assert_eq!(Grid::new(order[1..].to_vec()), None);
+
+        order[1] = Driver::VER;
+        assert_eq!(Grid::new(order), None);
+    }
+    #[test]
+    fn grid_penalty_drops_the_driver_back() {
+        let mut race = Race::new(Location::Monaco, 2022);
+        race.set_grid(vec![
+            Driver::LEC,
+            Driver::SAI,
+            Driver::PER,
+            Driver::VER,
+            Driver::NOR,
+        ]);
+
+        race.apply_grid_penalty(Driver::LEC, 3);
+        assert_eq!(race.start_position_of(Driver::LEC), Some(Position::new(4)));
+        assert_eq!(race.start_position_of(Driver::SAI), Some(Position::new(1)));
+        assert_eq!(race.start_position_of(Driver::VER), Some(Position::new(3)));
+        assert_eq!(race.start_position_of(Driver::NOR), Some(Position::new(5)));
+
+        race.apply_grid_penalty(Driver::SAI, 10);
+        assert_eq!(race.start_position_of(Driver::SAI), Some(Position::new(5)));
+    }
 }
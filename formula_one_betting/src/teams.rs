@@ -13,8 +13,26 @@ pub fn full_roster() -> HashMap<String, (Driver, Driver)> {
     ])
 }
 
+/// Both drivers of the team with the given name, e.g. "Red Bull" or "alfaromeo".
+/// Case and spaces are ignored, None for unknown teams
+pub fn team_drivers(name: &str) -> Option<(Driver, Driver)> {
+    let simplified = |name: &str| -> String {
+        name.chars()
+            .filter(|character| !character.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    full_roster()
+        .into_iter()
+        .find(|(team, _)| simplified(team) == simplified(name))
+        .map(|(_, drivers)| drivers)
+}
+
 /// List of all current drivers, can possibly change over time
 #[derive(Debug, Clone, Eq, PartialEq, Copy, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Driver {
     VER,
     PER,
@@ -38,6 +56,274 @@ pub enum Driver {
     ALB,
 }
 
+/// A set of drivers, cheap to copy since every driver is a single bit
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriverSet(u32);
+
+impl DriverSet {
+    pub fn insert(&mut self, driver: Driver) {
+        self.0 |= 1 << driver as u32;
+    }
+    pub fn contains(&self, driver: Driver) -> bool {
+        self.0 & (1 << driver as u32) != 0
+    }
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+    /// Drivers that are in both sets
+    pub fn intersection(&self, other: &DriverSet) -> DriverSet {
+        DriverSet(self.0 & other.0)
+    }
+    pub fn drivers(&self) -> Vec<Driver> {
+        Driver::all()
+            .into_iter()
+            .filter(|driver| self.contains(*driver))
+            .collect()
+    }
+}
+
+/// Only generates sets of actual drivers
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for DriverSet {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Vec::<Driver>::arbitrary(u)?.into_iter().collect())
+    }
+}
+
+impl FromIterator<Driver> for DriverSet {
+    fn from_iter<I: IntoIterator<Item = Driver>>(drivers: I) -> Self {
+        let mut set = DriverSet::default();
+        for driver in drivers {
+            set.insert(driver);
+        }
+        set
+    }
+}
+
+/// Every Constructors Team on the current grid
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "persistence", derive(serde::Serialize, serde::Deserialize))]
+pub enum Constructor {
+    RedBull,
+    Mercedes,
+    Ferrari,
+    Alpine,
+    McLaren,
+    AlfaRomeo,
+    AstonMartin,
+    Haas,
+    AlphaTauri,
+    Williams,
+}
+
+impl Constructor {
+    /// All constructors on the grid
+    pub fn all() -> [Constructor; 10] {
+        use Constructor::*;
+        [
+            RedBull,
+            Mercedes,
+            Ferrari,
+            Alpine,
+            McLaren,
+            AlfaRomeo,
+            AstonMartin,
+            Haas,
+            AlphaTauri,
+            Williams,
+        ]
+    }
+    /// The Team implementation backing this constructor
+    pub fn team(&self) -> Box<dyn Team> {
+        match self {
+            Constructor::RedBull => Box::new(RedBull::new()),
+            Constructor::Mercedes => Box::new(Mercedes::new()),
+            Constructor::Ferrari => Box::new(Ferrari::new()),
+            Constructor::Alpine => Box::new(Alpine::new()),
+            Constructor::McLaren => Box::new(McLaren::new()),
+            Constructor::AlfaRomeo => Box::new(AlfaRomeo::new()),
+            Constructor::AstonMartin => Box::new(AstonMartin::new()),
+            Constructor::Haas => Box::new(Haas::new()),
+            Constructor::AlphaTauri => Box::new(AlphaTauri::new()),
+            Constructor::Williams => Box::new(Williams::new()),
+        }
+    }
+    /// Primary livery color as RGB, handy to color code a frontend
+    pub fn color(&self) -> (u8, u8, u8) {
+        match self {
+            Constructor::RedBull => (54, 113, 198),
+            Constructor::Mercedes => (108, 211, 191),
+            Constructor::Ferrari => (249, 21, 54),
+            Constructor::Alpine => (34, 147, 209),
+            Constructor::McLaren => (245, 128, 32),
+            Constructor::AlfaRomeo => (201, 45, 75),
+            Constructor::AstonMartin => (53, 140, 117),
+            Constructor::Haas => (182, 186, 189),
+            Constructor::AlphaTauri => (94, 143, 170),
+            Constructor::Williams => (55, 190, 221),
+        }
+    }
+}
+
+impl Driver {
+    /// All drivers on the grid
+    pub fn all() -> [Driver; 20] {
+        use Driver::*;
+        [
+            VER, PER, LEC, SAI, HAM, RUS, ALO, OCO, NOR, RIC, BOT, ZHO, STR, VET, MSC, MAG, GAS,
+            TSU, LAT, ALB,
+        ]
+    }
+    /// The constructor this driver races for
+    pub fn team(&self) -> Constructor {
+        Constructor::all()
+            .into_iter()
+            .find(|team| {
+                let (first, second) = team.team().drivers();
+                first == *self || second == *self
+            })
+            .expect("Every driver races for a team")
+    }
+    /// The season of the first Grand Prix this driver started
+    pub fn debut_season(&self) -> u16 {
+        use Driver::*;
+        match self {
+            ALO => 2001,
+            HAM | VET => 2007,
+            PER | RIC => 2011,
+            BOT => 2013,
+            MAG => 2014,
+            VER | SAI => 2015,
+            OCO => 2016,
+            STR | GAS => 2017,
+            LEC => 2018,
+            RUS | NOR | ALB => 2019,
+            LAT => 2020,
+            MSC | TSU => 2021,
+            ZHO => 2022,
+        }
+    }
+    /// Whether the given season is the first season of this driver
+    pub fn is_rookie(&self, season: u16) -> bool {
+        self.debut_season() == season
+    }
+    /// Primary livery color of the team of this driver
+    pub fn team_color(&self) -> (u8, u8, u8) {
+        self.team().color()
+    }
+    /// The constructor this driver raced for in the given season,
+    /// None when the driver did not race that season or raced for a team that is gone.
+    /// Teams go by their current name, e.g. Toro Rosso seasons count as Alpha Tauri,
+    /// and a driver switching teams mid-season counts for the team of most races
+    pub fn team_in(&self, season: u16) -> Option<Constructor> {
+        self.career()
+            .iter()
+            .find(|(first, last, _)| (*first..=*last).contains(&season))
+            .map(|(_, _, team)| *team)
+    }
+    /// Every stint of the driver as first and last season with its constructor
+    fn career(&self) -> &'static [(u16, u16, Constructor)] {
+        use Constructor::*;
+        match self {
+            Driver::VER => &[(2015, 2015, AlphaTauri), (2016, 2022, RedBull)],
+            Driver::PER => &[
+                (2011, 2012, AlfaRomeo),
+                (2013, 2013, McLaren),
+                (2014, 2020, AstonMartin),
+                (2021, 2022, RedBull),
+            ],
+            Driver::LEC => &[(2018, 2018, AlfaRomeo), (2019, 2022, Ferrari)],
+            Driver::SAI => &[
+                (2015, 2017, AlphaTauri),
+                (2018, 2018, Alpine),
+                (2019, 2020, McLaren),
+                (2021, 2022, Ferrari),
+            ],
+            Driver::HAM => &[(2007, 2012, McLaren), (2013, 2022, Mercedes)],
+            Driver::RUS => &[(2019, 2021, Williams), (2022, 2022, Mercedes)],
+            Driver::ALO => &[
+                (2001, 2001, AlphaTauri),
+                (2003, 2006, Alpine),
+                (2007, 2007, McLaren),
+                (2008, 2009, Alpine),
+                (2010, 2014, Ferrari),
+                (2015, 2018, McLaren),
+                (2021, 2022, Alpine),
+            ],
+            Driver::OCO => &[(2017, 2018, AstonMartin), (2020, 2022, Alpine)],
+            Driver::NOR => &[(2019, 2022, McLaren)],
+            Driver::RIC => &[
+                (2012, 2013, AlphaTauri),
+                (2014, 2018, RedBull),
+                (2019, 2020, Alpine),
+                (2021, 2022, McLaren),
+            ],
+            Driver::BOT => &[
+                (2013, 2016, Williams),
+                (2017, 2021, Mercedes),
+                (2022, 2022, AlfaRomeo),
+            ],
+            Driver::ZHO => &[(2022, 2022, AlfaRomeo)],
+            Driver::STR => &[(2017, 2018, Williams), (2019, 2022, AstonMartin)],
+            Driver::VET => &[
+                (2007, 2008, AlphaTauri),
+                (2009, 2014, RedBull),
+                (2015, 2020, Ferrari),
+                (2021, 2022, AstonMartin),
+            ],
+            Driver::MSC => &[(2021, 2022, Haas)],
+            Driver::MAG => &[
+                (2014, 2014, McLaren),
+                (2016, 2016, Alpine),
+                (2017, 2020, Haas),
+                (2022, 2022, Haas),
+            ],
+            Driver::GAS => &[
+                (2017, 2018, AlphaTauri),
+                (2019, 2019, RedBull),
+                (2020, 2022, AlphaTauri),
+            ],
+            Driver::TSU => &[(2021, 2022, AlphaTauri)],
+            Driver::LAT => &[(2020, 2022, Williams)],
+            Driver::ALB => &[
+                (2019, 2019, AlphaTauri),
+                (2020, 2020, RedBull),
+                (2022, 2022, Williams),
+            ],
+        }
+    }
+}
+
+/// Parses the three letter code of a driver, in any case
+impl FromStr for Driver {
+    type Err = UnknownDriver;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Driver::all()
+            .into_iter()
+            .find(|driver| format!("{driver:?}").eq_ignore_ascii_case(code))
+            .ok_or_else(|| UnknownDriver { code: code.into() })
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnknownDriver {
+    pub code: String,
+}
+
+impl Error for UnknownDriver {}
+
+impl Display for UnknownDriver {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} is not a known driver code", self.code)
+    }
+}
+
 /// Each type implementing Team, can be considered a Constructors Team
 /// # Example
 /// Red Bull, Mercedes, ...
@@ -85,6 +371,78 @@ macro_rules! create_team {
     };
 }
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
 pub(crate) use create_team;
+
+#[cfg(test)]
+mod tests {
+    use super::{team_drivers, Constructor, Driver, DriverSet, UnknownDriver};
+
+    #[test]
+    fn every_constructor_has_a_distinct_color() {
+        let colors: Vec<(u8, u8, u8)> =
+            Constructor::all().iter().map(|team| team.color()).collect();
+
+        for (index, color) in colors.iter().enumerate() {
+            assert!(!colors[..index].contains(color));
+        }
+    }
+    #[test]
+    fn driver_color_matches_its_team() {
+        assert_eq!(Driver::VER.team(), Constructor::RedBull);
+        assert_eq!(Driver::LEC.team_color(), Constructor::Ferrari.color());
+        assert_eq!(Driver::ALB.team_color(), Constructor::Williams.color());
+    }
+    #[test]
+    fn driver_set_ignores_duplicates() {
+        let set: DriverSet = [Driver::VER, Driver::ALB, Driver::VER]
+            .into_iter()
+            .collect();
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(Driver::ALB));
+        assert!(!set.contains(Driver::HAM));
+        assert_eq!(set.drivers(), vec![Driver::VER, Driver::ALB]);
+    }
+    #[test]
+    fn rookies_are_in_their_debut_season() {
+        assert!(Driver::ZHO.is_rookie(2022));
+        assert!(!Driver::ZHO.is_rookie(2023));
+        assert!(!Driver::HAM.is_rookie(2022));
+    }
+    #[test]
+    fn team_drivers_are_found_by_any_spelling_of_the_team() {
+        assert_eq!(team_drivers("Red Bull"), Some((Driver::VER, Driver::PER)));
+        assert_eq!(team_drivers("alfa romeo"), Some((Driver::BOT, Driver::ZHO)));
+        assert_eq!(
+            team_drivers("AstonMartin"),
+            Some((Driver::STR, Driver::VET))
+        );
+        assert_eq!(team_drivers("Lotus"), None);
+    }
+    #[test]
+    fn drivers_changing_teams_race_for_another_team_per_season() {
+        assert_eq!(Driver::SAI.team_in(2020), Some(Constructor::McLaren));
+        assert_eq!(Driver::SAI.team_in(2021), Some(Constructor::Ferrari));
+        assert_eq!(Driver::ALO.team_in(2019), None);
+        assert_eq!(Driver::ZHO.team_in(2021), None);
+        for driver in Driver::all() {
+            assert_eq!(driver.team_in(2022), Some(driver.team()));
+        }
+    }
+    #[test]
+    fn drivers_parse_from_their_code() {
+        assert_eq!("VER".parse(), Ok(Driver::VER));
+        assert_eq!("lec".parse(), Ok(Driver::LEC));
+        assert_eq!(
+            "XYZ".parse::<Driver>(),
+            Err(UnknownDriver { code: "XYZ".into() })
+        );
+    }
+}